@@ -303,9 +303,11 @@ macro_rules! build_ecs {
 
         pub use self::_ecs_inner::ComponentNum;
 
+        // Components missing from serialized data are left empty, so adding new component
+        // types doesn't break old saves.
         #[derive(Serialize, Deserialize)]
         pub struct _ComponentStore {
-            $(pub $compname: $crate::ComponentData<$comptype>),+
+            $(#[serde(default)] pub $compname: $crate::ComponentData<$comptype>),+
         }
 
         impl ::std::default::Default for _ComponentStore {
@@ -360,7 +362,7 @@ macro_rules! build_ecs {
         /// entity.
         #[derive(Clone, Default, Debug, Eq, PartialEq, Serialize, Deserialize)]
         pub struct Loadout {
-            $(pub $compname: Option<$comptype>),+
+            $(#[serde(default)] pub $compname: Option<$comptype>),+
         }
 
         #[allow(dead_code)]
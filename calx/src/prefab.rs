@@ -24,19 +24,18 @@ pub enum PrefabError {
 }
 
 impl fmt::Display for PrefabError {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result { write!(f, "{}", self.to_string()) }
-}
-
-impl Error for PrefabError {
-    fn description(&self) -> &str {
-        match *self {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let text = match *self {
             PrefabError::InvalidInput => "Invalid input",
             PrefabError::MissingAnchor => "Anchor not found in input",
             PrefabError::MultipleAnchors => "Multiple anchor positions found in input",
-        }
+        };
+        write!(f, "{}", text)
     }
 }
 
+impl Error for PrefabError {}
+
 /// A trait for types that can be parsed into a map `Prefab`.
 ///
 /// # Examples
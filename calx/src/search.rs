@@ -18,10 +18,12 @@ pub struct Dijkstra<N> {
     pub weights: HashMap<N, u32>,
 }
 
-impl<N: GridNode> Dijkstra<N> {
-    /// Create a new Dijkstra map up to limit distance from goals, omitting
-    /// nodes for which the is_valid predicate returns false.
-    pub fn new<F: Fn(&N) -> bool>(goals: Vec<N>, is_valid: F, limit: u32) -> Dijkstra<N> {
+impl<N: Eq + Hash + Clone> Dijkstra<N> {
+    /// Create a new Dijkstra map over an arbitrary graph up to limit distance from goals.
+    ///
+    /// The neighbors function lists the nodes that can be stepped to from a node. Nodes that
+    /// shouldn't be part of the map must be left out of the neighbor lists.
+    pub fn from_fn<F: Fn(&N) -> Vec<N>>(goals: Vec<N>, neighbors: F, limit: u32) -> Dijkstra<N> {
         assert!(!goals.is_empty());
 
         let mut weights = HashMap::new();
@@ -38,8 +40,8 @@ impl<N: GridNode> Dijkstra<N> {
 
             let mut new_edge = HashSet::new();
             for n in &edge {
-                for m in n.neighbors() {
-                    if is_valid(&m) && !weights.contains_key(&m) {
+                for m in neighbors(n) {
+                    if !weights.contains_key(&m) {
                         new_edge.insert(m);
                     }
                 }
@@ -54,6 +56,18 @@ impl<N: GridNode> Dijkstra<N> {
 
         Dijkstra { weights }
     }
+}
+
+impl<N: GridNode> Dijkstra<N> {
+    /// Create a new Dijkstra map up to limit distance from goals, omitting
+    /// nodes for which the is_valid predicate returns false.
+    pub fn new<F: Fn(&N) -> bool>(goals: Vec<N>, is_valid: F, limit: u32) -> Dijkstra<N> {
+        Dijkstra::from_fn(
+            goals,
+            |n| n.neighbors().into_iter().filter(|m| is_valid(m)).collect(),
+            limit,
+        )
+    }
 
     /// Return the neighbors of a cell (if any), sorted from downhill to
     /// uphill.
@@ -172,6 +172,11 @@ pub fn entity_brushes() -> VecMap<Arc<Brush>> {
     ret.insert(Scroll1 as usize, Builder::new("assets/items.png").color(LIGHTYELLOW).item(0*16, 1*16).finish());
     ret.insert(Wand1 as usize, Builder::new("assets/items.png").color(RED).item(3*16, 0*16).finish());
     ret.insert(Wand2 as usize, Builder::new("assets/items.png").color(CYAN).item(3*16, 0*16).finish());
+    // TODO: Proper art for bows and arrows, using the wand sprite for now.
+    ret.insert(Bow as usize, Builder::new("assets/items.png").color(SADDLEBROWN).item(3*16, 0*16).finish());
+    ret.insert(Arrow as usize, Builder::new("assets/items.png").color(BURLYWOOD).item(3*16, 0*16).finish());
+    // TODO: Proper art for shields, using the armor sprite for now.
+    ret.insert(Shield as usize, Builder::new("assets/items.png").color(SILVER).item(6*16, 0*16).finish());
     ret
 }
 
@@ -226,5 +231,10 @@ pub fn misc_brushes() -> VecMap<Arc<Brush>> {
         .tile(6*32, 1*32).merge()
         .finish());
 
+    ret.insert(Projectile as usize, Builder::new("assets/fx.png")
+        .color(LIGHTGRAY)
+        .tile(6*32, 1*32)
+        .finish());
+
     ret
 }
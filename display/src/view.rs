@@ -16,7 +16,8 @@ use std::iter::FromIterator;
 use std::sync::Arc;
 use vitral::{color, Canvas};
 use world::{
-    AnimState, FovStatus, LerpLocation, Location, PhysicsSpace, PhysicsVector, Sector, World,
+    AnimState, FovStatus, LerpLocation, Location, PhysicsSpace, PhysicsVector, Sector, Status,
+    World,
 };
 
 /// Useful general constant for cell dimension ops.
@@ -253,6 +254,7 @@ impl WorldView {
                                 .color(coloring),
                         );
                         draw_health_pips(&mut entity_sprite_buffer, world, i, screen_pos);
+                        draw_status_pips(&mut entity_sprite_buffer, world, i, screen_pos);
                     }
                 }
 
@@ -360,6 +362,24 @@ impl WorldView {
                                 );
                             }
                         }
+
+                        if anim.state == AnimState::Projectile {
+                            let t = world.get_anim_tick() - anim.anim_start;
+
+                            if t <= anim.tween_duration as u64 {
+                                entity_sprite_buffer.push(
+                                    Sprite::new(
+                                        Layer::Effect,
+                                        screen_pos,
+                                        cache::misc(Icon::Projectile),
+                                    )
+                                    .color(Coloring::Shaded {
+                                        ambient,
+                                        diffuse: 1.0,
+                                    }),
+                                );
+                            }
+                        }
                     }
                 }
             }
@@ -439,6 +459,34 @@ impl WorldView {
             }
         }
 
+        fn draw_status_pips(
+            sprites: &mut Vec<Sprite>,
+            world: &World,
+            e: Entity,
+            screen_pos: ScreenVector,
+        ) {
+            let colors = world.statuses(e).into_iter().filter_map(|s| match s {
+                Status::Confused => Some(color::MAGENTA),
+                Status::Poisoned => Some(color::OLIVEDRAB),
+                Status::Regenerating => Some(color::PINK),
+                Status::Sleeping => Some(color::ROYALBLUE),
+                Status::Blind => Some(color::DIMGRAY),
+                Status::Afraid => Some(color::YELLOW),
+                Status::Slowed => Some(color::SADDLEBROWN),
+                Status::Hasted => Some(color::CYAN),
+                Status::Dead | Status::Delayed => None,
+            });
+
+            // Status pips go in a row under the health pips.
+            for (x, c) in colors.enumerate() {
+                let pos = screen_pos + vec2(x as i32 * 4 - 10, -6);
+                sprites.push(
+                    Sprite::new(Layer::Effect, pos, cache::misc(Icon::HealthPip))
+                        .color(Coloring::Solid(c)),
+                );
+            }
+        }
+
         /// Return vector to add to position if entity's position is being animated.
         fn lerp_offset(world: &World, e: Entity) -> ScreenVector {
             let loc = world.lerp_location(e).unwrap_or_else(Default::default);
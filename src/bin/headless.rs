@@ -0,0 +1,160 @@
+//! Run the game without a window.
+//!
+//! Reads player commands from stdin, one RON-encoded `Command` per line, and writes a RON-encoded
+//! `TurnReport` to stdout after each command. Game messages go to stderr.
+//!
+//! With `--bot`, a computer player makes the moves instead of stdin.
+//!
+//! With `--verify`, plays back a replay file and checks that it does not desync.
+
+use log::info;
+use rand::Rng;
+use std::io::{self, prelude::*};
+use std::path::PathBuf;
+use structopt::StructOpt;
+use world::{
+    Aggressor, Bot, Command, Explorer, ExternalEntity, MsgReceiver, RandomWalker, Replay, Runner,
+    WorldSeed, WorldSkeleton,
+};
+
+#[derive(Debug, StructOpt)]
+struct Opt {
+    #[structopt(long = "seed")]
+    seed: Option<u32>,
+    /// Stop after this many commands.
+    #[structopt(long = "max-turns")]
+    max_turns: Option<usize>,
+    /// Let a bot play the game: "random", "explorer" or "aggressor".
+    #[structopt(long = "bot")]
+    bot: Option<String>,
+    /// Play a straight dungeon dive instead of the overworld.
+    #[structopt(long = "dungeon")]
+    dungeon: bool,
+    /// Load the world from a RON skeleton file or a Tiled JSON map.
+    #[structopt(long = "world", parse(from_os_str))]
+    world: Option<PathBuf>,
+    /// Save a replay of the session to this file when done.
+    #[structopt(long = "record", parse(from_os_str))]
+    record: Option<PathBuf>,
+    /// Verify a replay file instead of playing.
+    #[structopt(long = "verify", parse(from_os_str))]
+    verify: Option<PathBuf>,
+}
+
+struct StderrReceiver;
+
+impl MsgReceiver for StderrReceiver {
+    fn msg(&self, text: &str) {
+        eprintln!("{}", text);
+    }
+}
+
+pub fn main() {
+    let opt = Opt::from_args();
+    env_logger::init();
+
+    world::register_msg_receiver(Box::new(StderrReceiver));
+
+    if let Some(path) = &opt.verify {
+        match Replay::load(path).and_then(|r| r.verify()) {
+            Ok(world) => eprintln!("Replay OK, {} ticks", world.get_tick()),
+            Err(e) => {
+                eprintln!("Replay {:?} failed: {}", path, e);
+                std::process::exit(1);
+            }
+        }
+        return;
+    }
+
+    let rng_seed = opt.seed.unwrap_or_else(|| rand::thread_rng().gen());
+    info!("World seed: {}", rng_seed);
+
+    let world_skeleton = if let Some(path) = &opt.world {
+        WorldSkeleton::load(path).unwrap_or_else(|e| {
+            eprintln!("Couldn't load world {:?}: {}", path, e);
+            std::process::exit(1);
+        })
+    } else if opt.dungeon {
+        WorldSkeleton::dungeon_dive()
+    } else {
+        WorldSkeleton::overworld_sprawl()
+    };
+
+    let mut runner = Runner::new(WorldSeed {
+        rng_seed,
+        world_skeleton,
+        player_character: ExternalEntity::from_name("player").unwrap(),
+    });
+
+    if let Some(name) = &opt.bot {
+        const DEFAULT_BOT_TURNS: usize = 10_000;
+
+        let mut bot: Box<dyn Bot> = match &name[..] {
+            "random" => Box::new(RandomWalker::new(rng_seed)),
+            "explorer" => Box::new(Explorer),
+            "aggressor" => Box::new(Aggressor::default()),
+            _ => {
+                eprintln!("Unknown bot {:?}", name);
+                std::process::exit(1);
+            }
+        };
+
+        let stdout = io::stdout();
+        let mut out = stdout.lock();
+        let outcome = runner.play(
+            &mut *bot,
+            opt.max_turns.unwrap_or(DEFAULT_BOT_TURNS),
+            |report| {
+                let _ = writeln!(out, "{}", ron::ser::to_string(report).unwrap());
+            },
+        );
+        eprintln!("Outcome: {:?}", outcome);
+        record(&opt, &runner);
+        return;
+    }
+
+    let stdin = io::stdin();
+    let stdout = io::stdout();
+    let mut out = stdout.lock();
+
+    for (turn, line) in stdin.lock().lines().enumerate() {
+        if opt.max_turns.is_some_and(|n| turn >= n) {
+            break;
+        }
+
+        let line = line.expect("Failed to read stdin");
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let cmd: Command = match ron::de::from_str(&line) {
+            Ok(cmd) => cmd,
+            Err(e) => {
+                eprintln!("Bad command {:?}: {}", line, e);
+                continue;
+            }
+        };
+
+        if !runner.command(cmd) {
+            eprintln!("Command {:?} rejected", cmd);
+        }
+        runner.wait_for_player();
+
+        let report = ron::ser::to_string(&runner.report()).unwrap();
+        let _ = writeln!(out, "{}", report);
+
+        if runner.world().game_over() {
+            break;
+        }
+    }
+
+    record(&opt, &runner);
+}
+
+fn record(opt: &Opt, runner: &Runner) {
+    if let Some(path) = &opt.record {
+        if let Err(e) = Replay::new(runner.history()).save(path) {
+            eprintln!("Couldn't save replay {:?}: {}", path, e);
+        }
+    }
+}
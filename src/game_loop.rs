@@ -1,15 +1,21 @@
+use crate::load_screen::LoadScreen;
 use crate::msg;
-use calx::{stego, CellVector, Dir6, IncrementalState};
+use calx::{CellVector, Dir6, IncrementalState};
 use calx_ecs::Entity;
 use display::{CanvasExt, ScreenVector};
 use euclid::default::{Point2D, Rect};
 use euclid::{point2, size2, vec2};
 use std::io::prelude::*;
-use std::io::Cursor;
 use vitral::{
     color, Align, ButtonAction, Canvas, InputEvent, Keycode, RectUtil, Rgba, Scene, SceneSwitch,
 };
-use world::{Ability, ActionOutcome, Command, LerpLocation, Slot, World, WorldSeed};
+use world::{
+    Ability, ActionOutcome, Command, LerpLocation, Replay, SaveError, SaveManager, Slot, World,
+    WorldSeed,
+};
+
+/// Save slot used by the quick save key.
+const QUICKSAVE_SLOT: &str = "quicksave";
 
 pub struct HotbarAction {
     ability: Ability,
@@ -23,22 +29,48 @@ pub(crate) struct GameRuntime {
     cursor_item: Option<Entity>,
     hotbar: [Option<HotbarAction>; 10],
     hotbar_focus: Option<usize>,
+    saves: SaveManager,
 }
 
 impl GameRuntime {
     pub fn new(seed: WorldSeed) -> GameRuntime {
-        GameRuntime {
+        let mut ret = GameRuntime {
             world: IncrementalState::new(seed),
             command: None,
             cursor_item: None,
             hotbar: Default::default(),
             hotbar_focus: None,
-        }
+            saves: SaveManager::user_saves(),
+        };
+        ret.bind_spells();
+        ret
+    }
+
+    pub fn saves(&self) -> &SaveManager { &self.saves }
+
+    /// Save the current game into a slot with a screenshot as the cover image.
+    pub fn save_game(&self, slot: &str, cover: &image::RgbImage) -> Result<(), SaveError> {
+        self.saves.save(slot, &self.world, cover)
+    }
+
+    /// Replace the current game with one loaded from a slot.
+    pub fn load_game(&mut self, slot: &str) -> Result<(), SaveError> {
+        self.world = self.saves.load(slot)?;
+        // UI state refers to entities in the old world.
+        self.command = None;
+        self.cursor_item = None;
+        self.hotbar = Default::default();
+        self.hotbar_focus = None;
+        self.bind_spells();
+        Ok(())
     }
 
     /// Method to force commands from eg. inventory mode
+    ///
+    /// Shows the reason in the message log if the command can't be done.
     pub fn force_command(&mut self, cmd: Command) -> bool {
-        if !self.world.can_command(&cmd) {
+        if let Err(err) = self.world.check_command(&cmd) {
+            msg::push(&err.to_string());
             return false;
         }
 
@@ -54,6 +86,31 @@ impl GameRuntime {
         true
     }
 
+    /// Bind spells known by the player to free hotbar slots.
+    fn bind_spells(&mut self) {
+        let player = match self.world.player() {
+            Some(player) => player,
+            None => return,
+        };
+
+        for ability in self.world.known_spells(player) {
+            if self
+                .hotbar
+                .iter()
+                .flatten()
+                .any(|a| a.item.is_none() && a.ability == ability)
+            {
+                continue;
+            }
+            if let Some(slot) = self.hotbar.iter_mut().find(|a| a.is_none()) {
+                *slot = Some(HotbarAction {
+                    ability,
+                    item: None,
+                });
+            }
+        }
+    }
+
     fn is_bindable_hotbar_action(&self, slot: usize) -> bool {
         match self.hotbar[slot] {
             Some(HotbarAction { ability, .. }) => ability.is_targeted(),
@@ -91,10 +148,20 @@ impl GameRuntime {
                         self.world.count(item),
                     );
                 }
-                Some(HotbarAction {
-                    ability: _ability, ..
-                }) => {
-                    // TODO: Icons for raw abilities
+                Some(HotbarAction { ability, .. }) => {
+                    // TODO: Icons for raw abilities, show the initial for now.
+                    let castable = self
+                        .world
+                        .player()
+                        .is_some_and(|p| self.world.can_cast(p, ability));
+                    let initial = format!("{:?}", ability)[..1].to_string();
+                    canvas.draw_text(
+                        &display::font(),
+                        pos + vec2(5, 4),
+                        Align::Left,
+                        if castable { color::CYAN } else { color::GRAY },
+                        &initial,
+                    );
                 }
             }
 
@@ -157,6 +224,8 @@ impl GameRuntime {
 pub struct GameLoop {
     pub console: display::Console,
     camera_loc: LerpLocation,
+    /// Next direction key fires a ranged attack instead of moving.
+    aiming: bool,
 }
 
 enum Side {
@@ -285,24 +354,27 @@ impl Scene<GameRuntime> for GameLoop {
         {
             use Keycode::*;
 
+            // Any key other than a direction cancels aiming.
+            let aiming = std::mem::replace(&mut self.aiming, false);
+
             match scancode {
                 Q | Pad7 | Home => {
-                    self.smart_step(ctx, Dir6::Northwest);
+                    self.direction_key(ctx, Dir6::Northwest, aiming);
                 }
                 W | Up | Pad8 => {
-                    self.smart_step(ctx, Dir6::North);
+                    self.direction_key(ctx, Dir6::North, aiming);
                 }
                 E | Pad9 | PageUp => {
-                    self.smart_step(ctx, Dir6::Northeast);
+                    self.direction_key(ctx, Dir6::Northeast, aiming);
                 }
                 A | Pad1 | End => {
-                    self.smart_step(ctx, Dir6::Southwest);
+                    self.direction_key(ctx, Dir6::Southwest, aiming);
                 }
                 S | Down | Pad2 => {
-                    self.smart_step(ctx, Dir6::South);
+                    self.direction_key(ctx, Dir6::South, aiming);
                 }
                 D | Pad3 | PageDown => {
-                    self.smart_step(ctx, Dir6::Southeast);
+                    self.direction_key(ctx, Dir6::Southeast, aiming);
                 }
                 Left | Pad4 => {
                     self.side_step(ctx, Side::West);
@@ -335,34 +407,49 @@ impl Scene<GameRuntime> for GameLoop {
                     ctx.command = Some(Command::Take);
                 }
 
+                F => {
+                    let can_shoot = ctx
+                        .world
+                        .player()
+                        .is_some_and(|p| ctx.world.stats(p).ranged_range > 0);
+                    if can_shoot {
+                        let _ = writeln!(&mut self.console, "Shoot in which direction?");
+                        self.aiming = true;
+                    } else {
+                        let _ = writeln!(&mut self.console, "You have no ranged attack.");
+                    }
+                }
+
                 Escape => {
                     return Some(SceneSwitch::Push(Box::new(InventoryScreen)));
                 }
                 F5 => {
                     // Quick save.
-
-                    let enc = ron::ser::to_string_pretty(&ctx.world, Default::default()).unwrap();
                     let cover = canvas.screenshot();
-                    let save = stego::embed_gzipped(&cover, enc.as_bytes());
-                    let _ = image::save_buffer(
-                        "save.png",
-                        &save,
-                        save.width(),
-                        save.height(),
-                        image::ColorType::Rgb8,
-                    );
+                    match ctx.save_game(QUICKSAVE_SLOT, &cover) {
+                        Ok(()) => {
+                            let _ = writeln!(&mut self.console, "Game saved.");
+                        }
+                        Err(e) => {
+                            let _ = writeln!(&mut self.console, "Couldn't save game: {}", e);
+                        }
+                    }
+                }
+                F6 => {
+                    // Save replay of the game so far.
+                    let replay = Replay::new(ctx.world.history());
+                    let path = ctx.saves().dir().join("replay.ron");
+                    match replay.save(&path) {
+                        Ok(()) => {
+                            let _ = writeln!(&mut self.console, "Replay saved to {:?}.", path);
+                        }
+                        Err(e) => {
+                            let _ = writeln!(&mut self.console, "Couldn't save replay: {}", e);
+                        }
+                    }
                 }
                 F9 => {
-                    // Quick load
-
-                    // TODO: Error handling when file is missing or not an image.
-                    let save = image::open("save.png").unwrap().to_rgb8();
-                    // TODO: Error handling when stego data can't be retrieved
-                    let save = stego::extract(&save).unwrap();
-                    // TODO: Error handling when stego data can't be deserialized into world
-                    let new_world: IncrementalState<World> =
-                        ron::de::from_reader(&mut Cursor::new(&save)).unwrap();
-                    ctx.world = new_world;
+                    return Some(SceneSwitch::Push(Box::new(LoadScreen::new(ctx.saves()))));
                 }
                 F12 => {
                     // Capture screenshot.
@@ -378,6 +465,15 @@ impl Scene<GameRuntime> for GameLoop {
 }
 
 impl GameLoop {
+    /// Shoot in direction if aiming, otherwise step.
+    fn direction_key(&self, ctx: &mut GameRuntime, dir: Dir6, aiming: bool) {
+        if aiming {
+            ctx.command = Some(Command::Shoot(dir));
+        } else {
+            self.smart_step(ctx, dir);
+        }
+    }
+
     /// Step command that turns into melee attack if an enemy is in the way.
     fn smart_step(&self, ctx: &mut GameRuntime, dir: Dir6) -> ActionOutcome {
         let player = ctx.world.player()?;
@@ -443,13 +539,27 @@ impl GameLoop {
 
     fn status_draw(&self, ctx: &mut GameRuntime, canvas: &mut Canvas, area: &Rect<i32>) {
         canvas.fill_rect(area, Rgba::from(0x33_11_11_ff));
-        canvas.draw_text(
-            &*display::font(),
-            area.origin,
-            Align::Left,
-            color::RED,
-            "Welcome to status bar",
-        );
+        if let Some(player) = ctx.world.player() {
+            let world = &ctx.world;
+            let mut status = format!("HP {}/{}", world.hp(player), world.max_hp(player));
+            if world.max_mana(player) > 0 {
+                status += &format!("  MP {}/{}", world.mana(player), world.max_mana(player));
+            }
+            status += &format!(
+                "  Level {}  XP {}/{}  Kills {}",
+                world.level(player),
+                world.xp(player),
+                world.xp_to_next_level(player),
+                world.kill_count()
+            );
+            canvas.draw_text(
+                &display::font(),
+                area.origin,
+                Align::Left,
+                color::RED,
+                &status,
+            );
+        }
 
         ctx.draw_hotbar(canvas);
     }
@@ -485,10 +595,9 @@ impl Scene<GameRuntime> for InventoryScreen {
                 }
                 Some(Place(e)) => {
                     // Putting it back where you took it, no-op but change UI.
-                    if let Some(old_slot) = ctx.world.entity_slot(e) {
-                        if old_slot == slot {
-                            ctx.cursor_item = None;
-                        }
+                    if ctx.world.entity_slot(e) == Some(slot) {
+                        ctx.cursor_item = None;
+                        return;
                     }
 
                     // Put in new slot, emit command
@@ -511,6 +620,9 @@ impl Scene<GameRuntime> for InventoryScreen {
             }
         }
 
+        // Slot under the mouse cursor, the item in it gets described.
+        let mut hovered = None;
+
         // Inventory items
         for y in 0..5 {
             for x in 0..10 {
@@ -520,6 +632,9 @@ impl Scene<GameRuntime> for InventoryScreen {
                 canvas.fill_rect(&bounds, color::BLACK);
 
                 let slot = Slot::Bag((x + y * 10) as u32);
+                if bounds.contains(canvas.mouse_pos()) {
+                    hovered = Some(slot);
+                }
 
                 let action = self.item_button(ctx, canvas, pos, slot);
                 handle_action(ctx, slot, action);
@@ -546,11 +661,26 @@ impl Scene<GameRuntime> for InventoryScreen {
             let bounds = Rect::new(pos, size2(16, 16));
             canvas.fill_rect(&bounds.inflate(1, 1), color::SILVER);
             canvas.fill_rect(&bounds, color::BLACK);
+            if bounds.contains(canvas.mouse_pos()) {
+                hovered = Some(slot);
+            }
 
             let action = self.item_button(ctx, canvas, pos, slot);
             handle_action(ctx, slot, action);
         }
 
+        if let Some(item) =
+            hovered.and_then(|slot| ctx.world.entity_equipped(ctx.world.player()?, slot))
+        {
+            canvas.draw_text(
+                &display::font(),
+                point2(8, 136),
+                Align::Left,
+                color::SILVER,
+                &ctx.world.item_description(item),
+            );
+        }
+
         ctx.draw_hotbar(canvas);
 
         // Draw cursor item as cursor
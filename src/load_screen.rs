@@ -0,0 +1,185 @@
+use crate::game_loop::GameRuntime;
+use euclid::point2;
+use vitral::{color, Align, Canvas, ImageKey, InputEvent, Keycode, Rgba, Scene, SceneSwitch};
+use world::{SaveManager, SaveSlot, THUMBNAIL_HEIGHT};
+
+/// How many save slots fit on screen at once.
+const VISIBLE_SLOTS: usize = 4;
+
+/// Scene for picking a saved game to load.
+pub struct LoadScreen {
+    slots: Vec<(SaveSlot, ImageKey)>,
+    selected: usize,
+    /// Error message from the last failed operation.
+    error: Option<String>,
+}
+
+impl LoadScreen {
+    pub fn new(saves: &SaveManager) -> LoadScreen {
+        let (slots, error) = match saves.list() {
+            Ok(slots) => (slots, None),
+            Err(e) => (Vec::new(), Some(e.to_string())),
+        };
+
+        let slots = slots
+            .into_iter()
+            .map(|slot| {
+                // Include timestamp in the id so an overwritten slot doesn't show a stale image.
+                let id = format!("save-thumbnail:{}:{:?}", slot.name, slot.modified);
+                let image = image::DynamicImage::ImageRgb8(slot.thumbnail.clone()).to_rgba8();
+                let key = vitral::add_sheet(id, image);
+                (slot, key)
+            })
+            .collect();
+
+        LoadScreen {
+            slots,
+            selected: 0,
+            error,
+        }
+    }
+
+    fn select(&mut self, offset: isize) {
+        if self.slots.is_empty() {
+            return;
+        }
+        let n = self.slots.len() as isize;
+        self.selected = (self.selected as isize + offset).rem_euclid(n) as usize;
+    }
+}
+
+impl Drop for LoadScreen {
+    fn drop(&mut self) {
+        // Thumbnails get new ids when saves change, don't let them pile up in the atlas.
+        for (_, key) in &self.slots {
+            vitral::remove_sheet(&key.id);
+        }
+    }
+}
+
+impl Scene<GameRuntime> for LoadScreen {
+    fn render(
+        &mut self,
+        _ctx: &mut GameRuntime,
+        canvas: &mut Canvas,
+    ) -> Option<SceneSwitch<GameRuntime>> {
+        let font = display::font();
+        let screen_area = canvas.screen_bounds();
+        canvas.fill_rect(&screen_area, Rgba::from(0x11_11_33_ff));
+
+        canvas.draw_text(
+            &font,
+            point2(8, 8),
+            Align::Left,
+            color::WHITE,
+            "Load game    UP/DOWN select  ENTER load  DEL delete  ESC back",
+        );
+
+        if self.slots.is_empty() {
+            canvas.draw_text(
+                &font,
+                point2(8, 32),
+                Align::Left,
+                color::GRAY,
+                "No saved games",
+            );
+        }
+
+        let first = self.selected.saturating_sub(VISIBLE_SLOTS - 1);
+        for (i, (slot, key)) in self
+            .slots
+            .iter()
+            .enumerate()
+            .skip(first)
+            .take(VISIBLE_SLOTS)
+        {
+            let y = 32 + ((i - first) as u32 * (THUMBNAIL_HEIGHT + 8)) as i32;
+            let text_color = if i == self.selected {
+                color::ORANGE
+            } else {
+                color::GRAY
+            };
+
+            if let Some(image) = vitral::get_image(key) {
+                canvas.draw_image(&image, point2(16, y), color::WHITE);
+            }
+
+            let modified = time::OffsetDateTime::from(slot.modified);
+            canvas.draw_text(&font, point2(160, y), Align::Left, text_color, &slot.name);
+            canvas.draw_text(
+                &font,
+                point2(160, y + 12),
+                Align::Left,
+                text_color,
+                &format!(
+                    "{}-{:02}-{:02} {:02}:{:02} UTC",
+                    modified.year(),
+                    modified.month() as u8,
+                    modified.day(),
+                    modified.hour(),
+                    modified.minute()
+                ),
+            );
+        }
+
+        if let Some(error) = &self.error {
+            canvas.draw_text(
+                &font,
+                point2(8, screen_area.max_y() - 16),
+                Align::Left,
+                color::RED,
+                error,
+            );
+        }
+
+        None
+    }
+
+    fn input(
+        &mut self,
+        ctx: &mut GameRuntime,
+        event: &InputEvent,
+        _canvas: &mut Canvas,
+    ) -> Option<SceneSwitch<GameRuntime>> {
+        if let InputEvent::KeyEvent {
+            is_down: true,
+            hardware_key: Some(scancode),
+            ..
+        } = event
+        {
+            use Keycode::*;
+
+            match scancode {
+                Up | Pad8 => self.select(-1),
+                Down | Pad2 => self.select(1),
+                Enter | PadEnter => {
+                    if let Some((slot, _)) = self.slots.get(self.selected) {
+                        match ctx.load_game(&slot.name) {
+                            Ok(()) => return Some(SceneSwitch::Pop),
+                            Err(e) => self.error = Some(e.to_string()),
+                        }
+                    }
+                }
+                Delete => {
+                    if let Some((slot, _)) = self.slots.get(self.selected) {
+                        match ctx.saves().delete(&slot.name) {
+                            Ok(()) => {
+                                let (_, key) = self.slots.remove(self.selected);
+                                vitral::remove_sheet(&key.id);
+                                self.selected =
+                                    self.selected.min(self.slots.len().saturating_sub(1));
+                                self.error = None;
+                            }
+                            Err(e) => self.error = Some(e.to_string()),
+                        }
+                    }
+                }
+                Escape => {
+                    return Some(SceneSwitch::Pop);
+                }
+                _ => {}
+            }
+        }
+        None
+    }
+}
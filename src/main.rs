@@ -2,19 +2,29 @@
 #![windows_subsystem = "windows"]
 
 use crate::game_loop::GameLoop;
+use crate::replay_view::ReplayView;
 use log::info;
 use rand::Rng;
+use std::path::PathBuf;
 use structopt::StructOpt;
-use vitral::{AppConfig, Flick};
-use world::{ExternalEntity, WorldSeed, WorldSkeleton};
+use vitral::{AppConfig, Flick, Scene};
+use world::{ExternalEntity, Replay, WorldSeed, WorldSkeleton};
 
 pub mod game_loop;
+mod load_screen;
 mod msg;
+mod replay_view;
 
 #[derive(Debug, StructOpt)]
 struct Opt {
     #[structopt(long = "seed")]
     seed: Option<u32>,
+    /// Play back a recorded replay file.
+    #[structopt(long = "replay", parse(from_os_str))]
+    replay: Option<PathBuf>,
+    /// Load the world from a RON skeleton file or a Tiled JSON map.
+    #[structopt(long = "world", parse(from_os_str))]
+    world: Option<PathBuf>,
 }
 
 pub fn main() {
@@ -27,21 +37,39 @@ pub fn main() {
 
     msg::register();
 
-    let rng_seed = opt.seed.unwrap_or_else(|| rand::thread_rng().gen());
-    // Print out the seed in case worldgen has a bug and we want to debug stuff with the same seed.
-    info!("World seed: {}", rng_seed);
+    let (world_seed, scene): (_, Box<dyn Scene<_>>) = if let Some(path) = opt.replay {
+        let replay = Replay::load(&path).unwrap_or_else(|e| {
+            eprintln!("Couldn't load replay {:?}: {}", path, e);
+            std::process::exit(1);
+        });
+        (replay.seed.clone(), Box::new(ReplayView::new(replay)))
+    } else {
+        let rng_seed = opt.seed.unwrap_or_else(|| rand::thread_rng().gen());
+        // Print out the seed in case worldgen has a bug and we want to debug stuff with the same
+        // seed.
+        info!("World seed: {}", rng_seed);
 
-    let world_seed = WorldSeed {
-        rng_seed,
-        world_skeleton: WorldSkeleton::overworld_sprawl(),
-        player_character: ExternalEntity::from_name("player").unwrap(),
+        let world_skeleton = match &opt.world {
+            Some(path) => WorldSkeleton::load(path).unwrap_or_else(|e| {
+                eprintln!("Couldn't load world {:?}: {}", path, e);
+                std::process::exit(1);
+            }),
+            None => WorldSkeleton::overworld_sprawl(),
+        };
+
+        let world_seed = WorldSeed {
+            rng_seed,
+            world_skeleton,
+            player_character: ExternalEntity::from_name("player").unwrap(),
+        };
+        (world_seed, Box::new(GameLoop::default()))
     };
 
     vitral::App::new(
         AppConfig::new(format!("Magog v{}", env!("CARGO_PKG_VERSION")))
             .frame_duration(Flick::from_seconds(1.0 / FPS)),
         game_loop::GameRuntime::new(world_seed),
-        vec![Box::new(GameLoop::default())],
+        vec![scene],
     )
     .run()
 }
@@ -14,14 +14,17 @@ lazy_static! {
 struct QueueReceiver;
 
 impl world::MsgReceiver for QueueReceiver {
-    fn msg(&self, text: &str) {
-        MSG_QUEUE
-            .lock()
-            .unwrap()
-            .borrow_mut()
-            .msgs
-            .push(text.to_string());
-    }
+    fn msg(&self, text: &str) { push(text); }
+}
+
+/// Add a message from the frontend itself to the queue.
+pub fn push(text: &str) {
+    MSG_QUEUE
+        .lock()
+        .unwrap()
+        .borrow_mut()
+        .msgs
+        .push(text.to_string());
 }
 
 pub fn get() -> Vec<String> { std::mem::take(&mut MSG_QUEUE.lock().unwrap().borrow_mut().msgs) }
@@ -0,0 +1,151 @@
+use crate::game_loop::GameRuntime;
+use crate::msg;
+use calx::Incremental;
+use std::io::prelude::*;
+use vitral::{color, Align, Canvas, InputEvent, Keycode, RectUtil, Rgba, Scene, SceneSwitch};
+use world::{LerpLocation, Replay, World};
+
+/// Scene that plays back a recorded game.
+pub struct ReplayView {
+    replay: Replay,
+    world: World,
+    /// Number of replay commands applied to world.
+    pos: usize,
+    paused: bool,
+    /// Set when the playback stops matching the recorded checksums.
+    desynced: bool,
+    console: display::Console,
+    camera_loc: LerpLocation,
+}
+
+impl ReplayView {
+    pub fn new(replay: Replay) -> ReplayView {
+        let world = World::from_seed(&replay.seed);
+        ReplayView {
+            replay,
+            world,
+            pos: 0,
+            paused: false,
+            desynced: false,
+            console: Default::default(),
+            camera_loc: Default::default(),
+        }
+    }
+
+    /// Apply the next recorded command.
+    fn step(&mut self) {
+        if let Some(cmd) = self.replay.commands.get(self.pos) {
+            self.world.update(cmd);
+            self.pos += 1;
+
+            if self.replay.matches(&self.world) == Some(false) {
+                let _ = writeln!(
+                    &mut self.console,
+                    "Replay desync at tick {}",
+                    self.world.get_tick()
+                );
+                self.desynced = true;
+                self.paused = true;
+            }
+        }
+    }
+
+    fn is_finished(&self) -> bool { self.pos >= self.replay.commands.len() }
+}
+
+impl Scene<GameRuntime> for ReplayView {
+    fn update(&mut self, _ctx: &mut GameRuntime) -> Option<SceneSwitch<GameRuntime>> {
+        for msg in &msg::get() {
+            let _ = writeln!(&mut self.console, "{}", msg);
+        }
+
+        if !self.paused {
+            self.step();
+        }
+        None
+    }
+
+    fn render(
+        &mut self,
+        _ctx: &mut GameRuntime,
+        canvas: &mut Canvas,
+    ) -> Option<SceneSwitch<GameRuntime>> {
+        let screen_area = canvas.screen_bounds();
+        let (view_area, status_area) = screen_area.horizontal_split(-32);
+
+        if let Some(loc) = self
+            .world
+            .player()
+            .and_then(|x| self.world.lerp_location(x))
+        {
+            self.camera_loc = loc;
+        }
+
+        let mut view = display::WorldView::new(self.camera_loc, view_area);
+        canvas.set_clip(view_area);
+        view.draw(&self.world, canvas);
+        canvas.clear_clip();
+
+        canvas.set_clip(status_area);
+        canvas.fill_rect(&status_area, Rgba::from(0x11_11_33_ff));
+        let state = if self.desynced {
+            "DESYNC"
+        } else if self.is_finished() {
+            "finished"
+        } else if self.paused {
+            "paused"
+        } else {
+            "playing"
+        };
+        canvas.draw_text(
+            &display::font(),
+            status_area.origin,
+            Align::Left,
+            color::CYAN,
+            &format!(
+                "Replay {}/{} tick {} [{}]  SPACE pause  RIGHT step  ESC quit",
+                self.pos,
+                self.replay.commands.len(),
+                self.world.get_tick(),
+                state
+            ),
+        );
+        canvas.clear_clip();
+
+        let mut console_area = screen_area;
+        console_area.size.height = 32;
+        self.console.draw_small(canvas, &console_area);
+
+        None
+    }
+
+    fn input(
+        &mut self,
+        _ctx: &mut GameRuntime,
+        event: &InputEvent,
+        _canvas: &mut Canvas,
+    ) -> Option<SceneSwitch<GameRuntime>> {
+        if let InputEvent::KeyEvent {
+            is_down: true,
+            hardware_key: Some(scancode),
+            ..
+        } = event
+        {
+            use Keycode::*;
+
+            match scancode {
+                Space => {
+                    self.paused = !self.paused;
+                }
+                Right | Pad6 if self.paused => {
+                    self.step();
+                }
+                Escape => {
+                    return Some(SceneSwitch::Pop);
+                }
+                _ => {}
+            }
+        }
+        None
+    }
+}
@@ -62,6 +62,27 @@ impl Atlas {
 
     pub fn size(&self) -> Size2D<u32> { size2(self.atlas.width(), self.atlas.height()) }
 
+    /// Free the atlas area of an image previously added with `add`.
+    ///
+    /// The freed area is not merged with adjacent free areas, so it can only be reused by images
+    /// that fit inside it.
+    pub fn remove(&mut self, image: &ImageData) {
+        debug_assert_eq!(image.texture, self.texture);
+        let origin = Point2D::new(
+            (image.tex_coords.origin.x * self.atlas.width() as f32).round() as u32,
+            (image.tex_coords.origin.y * self.atlas.height() as f32).round() as u32,
+        );
+        let area = Rect::new(origin, image.size);
+
+        if let Some(i) = self.placed.iter().position(|&r| r == area) {
+            self.placed.swap_remove(i);
+            self.slots.push(area);
+            self.slots.sort_by(|&a, &b| {
+                (a.size.width * a.size.height).cmp(&(b.size.width * b.size.height))
+            });
+        }
+    }
+
     /// Find the smallest slot in the slot vector that will fit the given item.
     ///
     /// Return `None` if the item will not fit in this atlas.
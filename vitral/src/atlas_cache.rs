@@ -167,6 +167,22 @@ impl<T: Eq + Hash + Clone + Debug> AtlasCache<T> {
         ret
     }
 
+    /// Remove a named tile source sheet and free the atlas space of its subimages.
+    pub fn remove_sheet(&mut self, id: &T) {
+        self.image_sheets.remove(id);
+
+        let keys: Vec<SubImageSpec<T>> = self
+            .atlas_images
+            .keys()
+            .filter(|k| &k.id == id)
+            .cloned()
+            .collect();
+        for key in keys {
+            let image = self.atlas_images.remove(&key).unwrap();
+            self.atlases[image.texture].remove(&image);
+        }
+    }
+
     /// Add a named tile source and generate tile data using image properties.
     ///
     /// Calls the `tilesheet_bounds` function to determine tilesheet subimages.
@@ -213,3 +229,25 @@ impl<T: Eq + Hash + Clone + Debug> AtlasCache<T> {
         FontData { chars, height }
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::AtlasCache;
+
+    #[test]
+    fn test_remove_sheet() {
+        let mut cache: AtlasCache<String> = Default::default();
+        let sheet = || image::RgbaImage::new(600, 600);
+
+        let key = cache.add_sheet("a", sheet());
+        let a = cache.get(&key).unwrap();
+        cache.remove_sheet(&"a".to_string());
+        assert!(cache.get(&key).is_none());
+
+        // The freed space gets reused instead of growing a new atlas.
+        let key = cache.add_sheet("b", sheet());
+        let b = cache.get(&key).unwrap();
+        assert_eq!(cache.atlases.len(), 1);
+        assert_eq!(a.tex_coords, b.tex_coords);
+    }
+}
@@ -20,7 +20,9 @@ pub use rect_util::RectUtil;
 mod scene;
 pub use scene::{InputEvent, Scene, SceneSwitch};
 mod state;
-pub use state::{add_sheet, add_tilesheet, add_tilesheet_font, get_frame_duration, get_image};
+pub use state::{
+    add_sheet, add_tilesheet, add_tilesheet_font, get_frame_duration, get_image, remove_sheet,
+};
 
 mod tilesheet;
 
@@ -28,6 +28,15 @@ pub fn add_sheet(id: impl Into<String>, sheet: impl Into<image::RgbaImage>) -> I
         .add_sheet(id, sheet)
 }
 
+/// Remove a named image and its subimages from the engine image atlas.
+pub fn remove_sheet(id: &str) {
+    ENGINE_STATE
+        .lock()
+        .unwrap()
+        .atlas_cache
+        .remove_sheet(&id.to_string())
+}
+
 /// Add a tilesheet image that gets automatically split to subimages based on image structure.
 ///
 /// Tiles are bounding boxes of non-background pixel groups surrounded by only background pixels or
@@ -9,8 +9,8 @@ use calx::{Dir6, RngExt};
 use calx_ecs::Entity;
 use serde::{Deserialize, Serialize};
 
-/// Used to determine who tries to fight whom.
-#[derive(Copy, Clone, Eq, PartialEq, Debug, Serialize, Deserialize)]
+/// Faction of a mob, used to determine who tries to fight whom.
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug, Serialize, Deserialize)]
 pub enum Alignment {
     /// Standard dungeon enemies, work in concert against the player.
     Enemy,
@@ -18,9 +18,40 @@ pub enum Alignment {
     Player,
     /// Indifferent or hungry, potential threat to Player and Enemy alike
     Animal,
+    /// Restless dead, hate all living things.
+    Undead,
 }
 
-#[derive(Copy, Clone, Eq, PartialEq, Debug, Serialize, Deserialize)]
+/// How mobs of one alignment treat mobs of another.
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug, Serialize, Deserialize)]
+pub enum Relation {
+    /// Attack on sight.
+    Hostile,
+    /// Leave alone unless provoked.
+    Neutral,
+    /// Never attack, help out in fights.
+    Allied,
+}
+
+impl Alignment {
+    /// Return the relation of this alignment towards another.
+    pub fn relation(self, other: Alignment) -> Relation {
+        use Alignment::*;
+        use Relation::*;
+        match (self, other) {
+            // Animals don't form packs, but they don't prey on each other either.
+            (Animal, Animal) => Neutral,
+            (a, b) if a == b => Allied,
+            // Everybody is out to get the player.
+            (Player, _) | (_, Player) => Hostile,
+            (Undead, _) | (_, Undead) => Hostile,
+            // Dungeon dwellers and wildlife tolerate each other.
+            _ => Neutral,
+        }
+    }
+}
+
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug, Serialize, Deserialize)]
 pub struct Brain {
     pub state: BrainState,
     pub alignment: Alignment,
@@ -48,6 +79,11 @@ impl Brain {
         self.shout = shout;
         self
     }
+
+    pub fn alignment(mut self, alignment: Alignment) -> Brain {
+        self.alignment = alignment;
+        self
+    }
 }
 
 impl Default for Brain {
@@ -61,7 +97,7 @@ impl Default for Brain {
 }
 
 /// Mob behavior state.
-#[derive(Copy, Clone, Eq, PartialEq, Debug, Serialize, Deserialize)]
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug, Serialize, Deserialize)]
 pub enum BrainState {
     /// AI mob is inactive, but can be startled into action by noise or
     /// motion.
@@ -70,12 +106,14 @@ pub enum BrainState {
     Hunting(Entity),
     /// Mob is wandering aimlessly
     Roaming,
+    /// Mob is a companion that stays close to its leader and fights the leader's enemies.
+    Following(Entity),
     /// Mob is under player control.
     PlayerControl,
 }
 
 /// How does a mob vocalize when alerted?
-#[derive(Copy, Clone, Eq, PartialEq, Debug, Serialize, Deserialize)]
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug, Serialize, Deserialize)]
 pub enum ShoutType {
     /// Humanoids
     Shout,
@@ -93,6 +131,20 @@ pub enum ShoutType {
     Silent,
 }
 
+impl ShoutType {
+    /// How far the shout can be heard.
+    pub fn loudness(self) -> u32 {
+        use ShoutType::*;
+        match self {
+            Roar => 12,
+            Shout | Bark => 10,
+            Buzz | Meow => 6,
+            Hiss | Gurgle | Squeak => 4,
+            Silent => 0,
+        }
+    }
+}
+
 impl World {
     /// Run AI for all autonomous mobs.
     pub(crate) fn ai_main(&mut self) {
@@ -121,18 +173,33 @@ impl World {
             }
         }
 
+        // Give up the hunt when the target is gone.
+        if let Some(Hunting(target)) = self.brain_state(npc) {
+            if !self.is_alive(target) {
+                self.ecs_mut().brain[npc].state = Roaming;
+            }
+        }
+
+        if self.has_status(npc, Status::Sleeping) {
+            return;
+        }
+
+        if self.has_status(npc, Status::Afraid) && self.is_active(npc) {
+            if let Some(threat) = self.find_enemy(npc) {
+                self.ai_flee(npc, threat);
+                return;
+            }
+        }
+
         let brain_state = self.brain_state(npc).expect("Running AI for non-mob");
         match brain_state {
             Asleep => {
-                // TODO: Noise waves that wake up sleeping mobs when they hit them, stop making
-                // each sleeping mob do active scanning every tic.
-                if let Some(e) = self.find_enemy(npc) {
-                    self.designate_enemy(npc, e);
-                }
+                // Sleeping mobs are woken up by noise, see `make_noise`.
             }
             Hunting(target) => {
                 if self.rng().one_chance_in(12) {
-                    self.ai_drift(npc);
+                    let target_loc = self.location(target);
+                    self.ai_drift(npc, target_loc);
                 } else {
                     self.ai_hunt(npc, target);
                 }
@@ -142,29 +209,149 @@ impl World {
                     self.designate_enemy(npc, e);
                     self.ai_hunt(npc, e);
                 } else {
-                    self.ai_drift(npc);
+                    self.ai_drift(npc, None);
                 }
             }
+            Following(leader) => self.ai_follow(npc, leader),
             PlayerControl => {}
         }
     }
 
+    /// Stay close to leader, attack enemies that come near.
+    fn ai_follow(&mut self, npc: Entity, leader: Entity) {
+        // Don't chase enemies further than this from leader.
+        const LEASH_DISTANCE: u32 = 6;
+        // Don't crowd leader closer than this.
+        const HEEL_DISTANCE: u32 = 2;
+
+        let (my_loc, leader_loc) = match (self.location(npc), self.location(leader)) {
+            (Some(a), Some(b)) => (a, b),
+            _ => {
+                // Leader is gone, go feral.
+                self.ecs_mut().brain[npc].state = BrainState::Roaming;
+                return;
+            }
+        };
+
+        let leader_distance = self.flow_distance(npc, leader_loc, my_loc);
+        if leader_distance.is_some_and(|d| d <= LEASH_DISTANCE) {
+            if let Some(enemy) = self.find_enemy(npc) {
+                self.ai_hunt(npc, enemy);
+                return;
+            }
+        }
+
+        if leader_distance.is_some_and(|d| d <= HEEL_DISTANCE) {
+            if self.rng().one_chance_in(4) {
+                self.ai_drift(npc, Some(leader_loc));
+            }
+        } else if let Some(dir) = self.pathing_dir_towards(npc, leader_loc) {
+            let _ = self.entity_step(npc, dir);
+        }
+    }
+
     /// Approach and attack target entity.
     fn ai_hunt(&mut self, npc: Entity, target: Entity) {
+        if self.stats(npc).ranged_range > 0 && self.ai_skirmish(npc, target) {
+            return;
+        }
+
         if let (Some(my_loc), Some(target_loc)) = (self.location(npc), self.location(target)) {
             if my_loc.metric_distance(target_loc) == 1 {
                 let _ = self.entity_melee(npc, my_loc.dir6_towards(target_loc).unwrap());
             } else if let Some(move_dir) = self.pathing_dir_towards(npc, target_loc) {
                 let _ = self.entity_step(npc, move_dir);
             } else {
-                self.ai_drift(npc);
+                self.ai_drift(npc, Some(target_loc));
             }
         }
     }
 
+    /// Run away from a threat, fight back if cornered.
+    fn ai_flee(&mut self, npc: Entity, threat: Entity) {
+        let (my_loc, threat_loc) = match (self.location(npc), self.location(threat)) {
+            (Some(a), Some(b)) => (a, b),
+            _ => return,
+        };
+        let current = match self.flow_distance(npc, threat_loc, my_loc) {
+            Some(d) => d,
+            // Out of reach already.
+            None => return,
+        };
+
+        let away = Dir6::iter()
+            .cloned()
+            .filter_map(|dir| {
+                let loc = my_loc.jump(self, dir);
+                if !self.can_enter(npc, loc) {
+                    return None;
+                }
+                // Stepping out of the flow field is getting as far as you can.
+                let dist = self.flow_distance(npc, threat_loc, loc).unwrap_or(u32::MAX);
+                if dist > current {
+                    Some((dist, dir))
+                } else {
+                    None
+                }
+            })
+            .max_by_key(|&(dist, _)| dist)
+            .map(|(_, dir)| dir);
+
+        if let Some(dir) = away {
+            let _ = self.entity_step(npc, dir);
+        } else if my_loc.metric_distance(threat_loc) == 1 {
+            let _ = self.entity_melee(npc, my_loc.dir6_towards(threat_loc).unwrap());
+        }
+    }
+
+    /// Keep distance to target and attack it from range.
+    ///
+    /// Return whether the mob did something.
+    fn ai_skirmish(&mut self, npc: Entity, target: Entity) -> bool {
+        // Ranged attackers try to keep at least this far from their target.
+        const PREFERRED_DISTANCE: i32 = 3;
+
+        let (my_loc, target_loc) = match (self.location(npc), self.location(target)) {
+            (Some(a), Some(b)) => (a, b),
+            _ => return false,
+        };
+        let distance = my_loc.metric_distance(target_loc);
+
+        if distance < PREFERRED_DISTANCE {
+            let retreat = Dir6::iter().cloned().find(|&dir| {
+                let loc = my_loc.jump(self, dir);
+                loc.metric_distance(target_loc) > distance && self.can_enter(npc, loc)
+            });
+            if let Some(dir) = retreat {
+                return self.entity_step(npc, dir).is_some();
+            }
+        }
+
+        let range = self.stats(npc).ranged_range as usize;
+        let line_of_fire = Dir6::iter()
+            .cloned()
+            .find(|&dir| self.find_ranged_target(npc, dir, range) == Some(target));
+        if let Some(dir) = line_of_fire {
+            return self.entity_shoot(npc, dir).is_some();
+        }
+
+        false
+    }
+
     /// Wander around aimlessly
-    fn ai_drift(&mut self, npc: Entity) {
-        let dirs = Dir6::permuted_dirs(self.rng());
+    ///
+    /// If the mob has a target, steps that don't take it further away from the target are tried
+    /// first.
+    fn ai_drift(&mut self, npc: Entity, target: Option<Location>) {
+        let mut dirs = Dir6::permuted_dirs(self.rng());
+        if let (Some(origin), Some(target)) = (self.location(npc), target) {
+            if let Some(current) = self.flow_distance(npc, target, origin) {
+                dirs.sort_by_key(|&dir| {
+                    self.flow_distance(npc, target, origin.jump(self, dir))
+                        .is_none_or(|d| d > current)
+                });
+            }
+        }
         for &dir in &dirs {
             if self.entity_step(npc, dir).is_some() {
                 return;
@@ -189,29 +376,18 @@ impl World {
             }
         }
 
-        // TODO: Possibility to pick other targets than player
-        // TODO: Creatures that are friendly or neutral to player
-
-        if let (Some(loc), Some(player), Some(player_loc)) = (
-            self.location(npc),
-            self.player(),
-            self.player().map(|p| self.location(p)).unwrap_or(None),
-        ) {
-            if self.player_sees(loc) {
-                // Okay, tricky spot. Player might be seeing mob across a portal, in
-                // which case we can't do naive distance check.
-                // This could have a helper method that finds chart distance to self in
-                // player's map memory.
-                //
-                // For now, let's just go with mobs past portals not waking up to
-                // player.
-
-                if loc.metric_distance(player_loc) <= WAKEUP_DISTANCE {
-                    return Some(player);
-                }
-            }
-        }
-        None
+        // Go after the closest hostile mob in sight.
+        let loc = self.location(npc)?;
+        let range = if self.has_status(npc, Status::Blind) {
+            1
+        } else {
+            WAKEUP_DISTANCE
+        };
+        self.fov_from(loc, range)
+            .into_iter()
+            .filter_map(|seen| self.mob_at(seen))
+            .filter(|&e| e != npc && self.is_hostile_to(npc, e))
+            .min_by_key(|&e| self.distance_between(npc, e).unwrap_or(i32::MAX))
     }
 
     /// End move for entity.
@@ -223,29 +399,72 @@ impl World {
     }
 
     pub(crate) fn notify_attacked_by(&mut self, victim: Entity, attacker: Entity) {
-        // TODO: Check if victim is already in close combat and don't disengage against new target
-        // if it is.
-        // TODO: The idea is to do Doom-style thing where friendly fire will occasionally cause
-        // infighting between enemies, but it should be rare.
+        if victim == attacker {
+            return;
+        }
+
+        // Companions don't turn on their leader over friendly fire.
+        if let Some(BrainState::Following(_)) = self.brain_state(victim) {
+            return;
+        }
+
+        // Don't disengage from a fight in progress to chase after a new attacker.
+        if let Some(BrainState::Hunting(current)) = self.brain_state(victim) {
+            let engaged = |e| self.distance_between(victim, e) == Some(1);
+            if current != attacker
+                && self.is_hostile_to(victim, current)
+                && engaged(current)
+                && !engaged(attacker)
+            {
+                return;
+            }
+        }
+
+        // Doom-style infighting, getting hit by friendly fire makes the victim turn on the attacker
+        // regardless of alignment.
         self.designate_enemy(victim, attacker);
     }
 
-    fn designate_enemy(&mut self, e: Entity, target: Entity) {
+    pub(crate) fn designate_enemy(&mut self, e: Entity, target: Entity) {
         // TODO: Probably want this logic to be more complex eventually.
         if self.is_npc(e) {
-            if self.brain_state(e) == Some(BrainState::Asleep) {
-                self.shout(e);
-            }
+            let was_asleep = self.brain_state(e) == Some(BrainState::Asleep);
             if let Some(brain) = self.ecs_mut().brain.get_mut(e) {
                 brain.state = BrainState::Hunting(target);
             }
+            // Shout after waking up so the shout's noise doesn't wake this mob again.
+            if was_asleep {
+                self.shout(e);
+            }
+        }
+    }
+
+    /// Make a mob into a companion of the leader.
+    ///
+    /// The companion joins the leader's faction and starts following the leader around.
+    pub(crate) fn make_companion(&mut self, e: Entity, leader: Entity) {
+        let alignment = self.alignment(leader);
+        if let (Some(brain), Some(alignment)) = (self.ecs_mut().brain.get_mut(e), alignment) {
+            brain.alignment = alignment;
+            brain.state = BrainState::Following(leader);
+        }
+    }
+
+    /// Return the leader of a companion mob.
+    pub fn leader(&self, e: Entity) -> Option<Entity> {
+        match self.brain_state(e) {
+            Some(BrainState::Following(leader)) => Some(leader),
+            _ => None,
         }
     }
 
     /// Make a mob shout according to its type.
     pub(crate) fn shout(&mut self, e: Entity) {
-        // TODO: Create noise, wake up other nearby monsters.
         if let Some(shout) = self.ecs().brain.get(e).map(|b| b.shout) {
+            if let Some(loc) = self.location(e) {
+                self.make_noise(loc, shout.loudness(), Some(e));
+            }
+
             match shout {
                 ShoutType::Shout => {
                     msg!("[One] shout[s] angrily."; self.subject(e));
@@ -280,7 +499,7 @@ impl World {
     pub fn is_mob(&self, e: Entity) -> bool { self.ecs().brain.contains(e) }
 
     /// Return the AI state of an entity.
-    fn brain_state(&self, e: Entity) -> Option<BrainState> {
+    pub(crate) fn brain_state(&self, e: Entity) -> Option<BrainState> {
         self.ecs().brain.get(e).map(|brain| brain.state)
     }
 
@@ -360,7 +579,7 @@ impl World {
 
     /// Return whether the entity is a mob that will act this frame.
     pub fn acts_this_frame(&self, e: Entity) -> bool {
-        if !self.is_active(e) {
+        if !self.is_active(e) || self.has_status(e, Status::Sleeping) {
             return false;
         }
         self.ticks_this_frame(e)
@@ -382,19 +601,19 @@ impl World {
         }
 
         if let Some(BrainState::Hunting(target)) = self.brain_state(npc) {
-            if npc == target {
+            if other == target {
                 // Already beating him up, obviously he must've done something bad to make you
                 // fight him.
                 return true;
             }
         }
 
-        let (a, b) = (self.alignment(npc), self.alignment(other));
-        if a.is_none() || b.is_none() {
-            return false;
-        }
+        self.relation(npc, other) == Some(Relation::Hostile)
+    }
 
-        a != b
+    /// Return how the entity's faction regards the other entity's faction.
+    pub fn relation(&self, e: Entity, other: Entity) -> Option<Relation> {
+        Some(self.alignment(e)?.relation(self.alignment(other)?))
     }
 
     /// Look for targets to shoot in a direction.
@@ -407,9 +626,8 @@ impl World {
                 break;
             }
             if let Some(e) = self.mob_at(loc) {
-                if self.is_hostile_to(shooter, e) {
-                    return Some(e);
-                }
+                // Projectiles stop at the first mob on the way.
+                return Some(e).filter(|&e| self.is_hostile_to(shooter, e));
             }
         }
         None
@@ -417,10 +635,13 @@ impl World {
 
     /// Try to get the next step on the path from origin towards destination.
     ///
-    /// Tries to be fast, not necessarily doing proper pathfinding.
+    /// Follows the flow field towards destination when there is one, otherwise just tries to head
+    /// in the general direction of the destination.
     pub(crate) fn pathing_dir_towards(&self, e: Entity, destination: Location) -> Option<Dir6> {
-        // Could do all sorts of cool things here eventually like a Dijkstra map cache, but for now
-        // just doing very simple stuff.
+        if let Some(dir) = self.flow_dir(e, destination) {
+            return Some(dir);
+        }
+
         if let Some(origin) = self.location(e) {
             if let Some(dir) = origin.dir6_towards(destination) {
                 // Try direct approach, the the other directions.
@@ -450,3 +671,170 @@ impl World {
             .collect()
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::{Alignment, BrainState, Relation};
+    use crate::stats::Status;
+    use crate::{Command, ExternalEntity, World, WorldSeed, WorldSkeleton};
+    use calx::{Dir6, Incremental};
+
+    #[test]
+    fn test_relation_matrix() {
+        use Alignment::*;
+        let all = [Enemy, Player, Animal, Undead];
+        for &a in &all {
+            for &b in &all {
+                assert_eq!(a.relation(b), b.relation(a));
+            }
+        }
+        assert_eq!(Enemy.relation(Enemy), Relation::Allied);
+        assert_eq!(Enemy.relation(Player), Relation::Hostile);
+        assert_eq!(Enemy.relation(Undead), Relation::Hostile);
+        assert_eq!(Animal.relation(Animal), Relation::Neutral);
+    }
+
+    #[test]
+    fn test_infighting() {
+        let mut world = World::from_seed(&WorldSeed {
+            rng_seed: 1,
+            world_skeleton: WorldSkeleton::dungeon_dive(),
+            player_character: ExternalEntity::from_name("player").unwrap(),
+        });
+        let player = world.player().unwrap();
+        let origin = world.location(player).unwrap();
+
+        // Find an open pair of cells away from the player and other mobs.
+        let (loc, dir) = world
+            .noise_area(origin, 30)
+            .into_iter()
+            .filter(|&loc| {
+                loc.metric_distance(origin) > 10
+                    && world
+                        .noise_area(loc, 6)
+                        .iter()
+                        .all(|&x| world.mob_at(x).is_none())
+            })
+            .find_map(|loc| {
+                Dir6::iter()
+                    .find(|&&d| !world.terrain_blocks_walk(loc.jump(&world, d)))
+                    .map(|&d| (loc, d))
+            })
+            .expect("No room for test");
+
+        let dreg = world.spawn(&ExternalEntity::from_name("dreg").unwrap(), loc);
+        let snake = world.spawn(
+            &ExternalEntity::from_name("snake").unwrap(),
+            loc.jump(&world, dir),
+        );
+        world.ecs_mut().brain[dreg].state = BrainState::Roaming;
+
+        // Dungeon dwellers leave wildlife alone.
+        assert!(!world.is_hostile_to(dreg, snake));
+        assert_eq!(world.find_enemy(dreg), None);
+
+        // Until it bites them.
+        world.notify_attacked_by(dreg, snake);
+        assert_eq!(world.brain_state(dreg), Some(BrainState::Hunting(snake)));
+        assert!(world.is_hostile_to(dreg, snake));
+
+        // Undead are fair game on sight.
+        world.ecs_mut().brain[dreg].state = BrainState::Roaming;
+        world.kill_entity(snake);
+        let wraith = world.spawn(
+            &ExternalEntity::from_name("wraith").unwrap(),
+            loc.jump(&world, dir),
+        );
+        assert_eq!(world.find_enemy(dreg), Some(wraith));
+    }
+
+    #[test]
+    fn test_companion() {
+        let mut world = World::from_seed(&WorldSeed {
+            rng_seed: 1,
+            world_skeleton: WorldSkeleton::dungeon_dive(),
+            player_character: ExternalEntity::from_name("player").unwrap(),
+        });
+        let player = world.player().unwrap();
+        let origin = world.location(player).unwrap();
+
+        // Wild mobs around the start would pick fights with the dog.
+        for e in world.active_mobs() {
+            if e != player {
+                world.kill_entity(e);
+            }
+        }
+
+        let dir = *Dir6::iter()
+            .find(|&&d| !world.blocks_walk(origin.jump(&world, d)))
+            .expect("No room for test");
+        let dog = world.spawn(
+            &ExternalEntity::from_name("dog").unwrap(),
+            origin.jump(&world, dir),
+        );
+        world.make_companion(dog, player);
+        assert_eq!(world.leader(dog), Some(player));
+        assert!(!world.is_hostile_to(dog, player));
+        assert!(!world.is_hostile_to(player, dog));
+
+        // Player swaps places with companion instead of bumping into it.
+        assert!(world.really_step(player, dir).is_some());
+        assert_eq!(world.location(player), Some(origin.jump(&world, dir)));
+        assert_eq!(world.location(dog), Some(origin));
+
+        // Companion catches up when player moves away.
+        let far = *world.noise_area(origin, 6).last().unwrap();
+        world.place_entity(player, far);
+        for _ in 0..100 {
+            while !world.player_can_act() {
+                world.update(&Command::Wait);
+            }
+            world.update(&Command::Pass);
+        }
+        assert!(world.distance_between(dog, player).unwrap() <= 3);
+
+        // Companion survives save and load.
+        let saved = ron::ser::to_string(&world).unwrap();
+        let loaded: World = ron::de::from_str(&saved).unwrap();
+        assert_eq!(loaded.leader(dog), Some(player));
+    }
+
+    #[test]
+    fn test_fear() {
+        let mut world = World::from_seed(&WorldSeed {
+            rng_seed: 1,
+            world_skeleton: WorldSkeleton::dungeon_dive(),
+            player_character: ExternalEntity::from_name("player").unwrap(),
+        });
+        let player = world.player().unwrap();
+        let origin = world.location(player).unwrap();
+
+        // Find an open pair of cells away from other mobs.
+        let (loc, dir) = world
+            .noise_area(origin, 30)
+            .into_iter()
+            .filter(|&loc| {
+                world
+                    .noise_area(loc, 6)
+                    .iter()
+                    .all(|&x| world.mob_at(x).is_none() || x == origin)
+            })
+            .find_map(|loc| {
+                Dir6::iter()
+                    .find(|&&d| !world.terrain_blocks_walk(loc.jump(&world, d)))
+                    .map(|&d| (loc, d))
+            })
+            .expect("No room for test");
+
+        world.place_entity(player, loc);
+        let dreg = world.spawn(
+            &ExternalEntity::from_name("dreg").unwrap(),
+            loc.jump(&world, dir),
+        );
+        world.designate_enemy(dreg, player);
+
+        world.gain_status(dreg, Status::Afraid, 100);
+        world.run_ai_for(dreg);
+        assert_eq!(world.distance_between(dreg, player), Some(2));
+    }
+}
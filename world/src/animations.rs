@@ -5,6 +5,7 @@ use calx::{ease, project, CellSpace, ProjectVec, Space};
 use calx_ecs::Entity;
 use euclid::{vec2, Vector2D, Vector3D};
 use serde::{Deserialize, Serialize};
+use std::hash::{Hash, Hasher};
 
 /// Location with a non-integer offset delta.
 ///
@@ -88,7 +89,22 @@ pub struct Anim {
     pub state: AnimState,
 }
 
-#[derive(Eq, PartialEq, Copy, Clone, Debug, Serialize, Deserialize)]
+/// Only hashes the fields that are set from the world clock.
+///
+/// The animation clock runs at display speed, so it must stay out of game state hashes.
+impl Hash for Anim {
+    fn hash<H: Hasher>(&self, h: &mut H) {
+        (
+            self.tween_from,
+            self.tween_duration,
+            self.anim_done_world_tick,
+            self.state,
+        )
+            .hash(h);
+    }
+}
+
+#[derive(Eq, PartialEq, Hash, Copy, Clone, Debug, Serialize, Deserialize)]
 pub enum AnimState {
     /// Mob decorator, doing nothing in particular
     Mob,
@@ -104,6 +120,8 @@ pub enum AnimState {
     Explosion,
     /// Pre-exploded fireball
     Firespell,
+    /// Arrow or other missile in flight
+    Projectile,
 }
 
 impl AnimState {
@@ -113,7 +131,7 @@ impl AnimState {
         use AnimState::*;
         match self {
             Mob | MobHurt | MobBlocks => false,
-            Gib | Smoke | Explosion | Firespell => true,
+            Gib | Smoke | Explosion | Firespell | Projectile => true,
         }
     }
 }
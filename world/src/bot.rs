@@ -0,0 +1,152 @@
+//! Computer players for automated playtesting
+
+use crate::{Command, Location, Rng, World};
+use calx::{seeded_rng, Dir6};
+use calx_ecs::Entity;
+use rand::Rng as _;
+use std::collections::{HashSet, VecDeque};
+
+/// Automated player that decides what the player character does next.
+pub trait Bot {
+    /// Choose the next command.
+    ///
+    /// Only called when the player character is alive and can act.
+    fn act(&mut self, world: &World) -> Command;
+}
+
+/// Stumbles around in random directions, hitting whatever gets in the way.
+pub struct RandomWalker {
+    rng: Rng,
+}
+
+impl RandomWalker {
+    pub fn new(seed: u32) -> RandomWalker {
+        RandomWalker {
+            rng: seeded_rng(&seed),
+        }
+    }
+}
+
+impl Bot for RandomWalker {
+    fn act(&mut self, world: &World) -> Command {
+        let player = world.player().expect("Bot acting without player");
+        step_or_attack(world, player, self.rng.gen())
+    }
+}
+
+/// Walks towards the nearest unexplored area and heads downstairs once everything is seen.
+///
+/// Fights back against enemies that block the way.
+#[derive(Default)]
+pub struct Explorer;
+
+impl Bot for Explorer {
+    fn act(&mut self, world: &World) -> Command {
+        let player = world.player().expect("Bot acting without player");
+        if let Some(dir) = adjacent_enemy(world, player) {
+            return Command::Melee(dir);
+        }
+
+        let unexplored = path_step(world, player, |loc, dir| {
+            world.fov_status(loc.jump(world, dir)).is_none()
+        });
+        let downstairs = || path_step(world, player, |loc, dir| loc.jump(world, dir).z < loc.z);
+
+        match unexplored.or_else(downstairs) {
+            Some(dir) => step_or_attack(world, player, dir),
+            None => Command::Pass,
+        }
+    }
+}
+
+/// Charges at the closest visible enemy, explores when there is nothing to fight.
+#[derive(Default)]
+pub struct Aggressor {
+    explorer: Explorer,
+}
+
+impl Bot for Aggressor {
+    fn act(&mut self, world: &World) -> Command {
+        let player = world.player().expect("Bot acting without player");
+        if let Some(dir) = adjacent_enemy(world, player) {
+            return Command::Melee(dir);
+        }
+
+        let target = world
+            .entities()
+            .filter(|&&e| {
+                world.is_hostile_to(player, e)
+                    && world.location(e).is_some_and(|loc| world.player_sees(loc))
+            })
+            .min_by_key(|&&e| world.distance_between(player, e).unwrap_or(i32::MAX))
+            .cloned();
+
+        if let Some(loc) = target.and_then(|e| world.location(e)) {
+            if let Some(dir) = world.pathing_dir_towards(player, loc) {
+                return step_or_attack(world, player, dir);
+            }
+        }
+
+        self.explorer.act(world)
+    }
+}
+
+/// Return direction of an adjacent mob the entity wants to fight.
+fn adjacent_enemy(world: &World, e: Entity) -> Option<Dir6> {
+    let loc = world.location(e)?;
+    Dir6::iter().cloned().find(|&dir| {
+        world
+            .mob_at(loc.jump(world, dir))
+            .is_some_and(|mob| world.is_hostile_to(e, mob))
+    })
+}
+
+/// Melee attack if there is a hostile mob in the direction, step otherwise.
+fn step_or_attack(world: &World, e: Entity, dir: Dir6) -> Command {
+    let hostile = world
+        .location(e)
+        .and_then(|loc| world.mob_at(loc.jump(world, dir)))
+        .is_some_and(|mob| world.is_hostile_to(e, mob));
+    if hostile {
+        Command::Melee(dir)
+    } else {
+        Command::Step(dir)
+    }
+}
+
+/// Find the first step of the shortest path over known walkable terrain towards a goal.
+///
+/// The goal predicate is given a location on the path and a direction to step from it.
+fn path_step(world: &World, e: Entity, is_goal: impl Fn(Location, Dir6) -> bool) -> Option<Dir6> {
+    // Don't let the search flood the entire overworld.
+    const SEARCH_LIMIT: usize = 10_000;
+
+    let origin = world.location(e)?;
+    let mut seen = HashSet::new();
+    let mut edge = VecDeque::new();
+    seen.insert(origin);
+    edge.push_back((origin, None));
+
+    while let Some((loc, first_step)) = edge.pop_front() {
+        if seen.len() > SEARCH_LIMIT {
+            break;
+        }
+
+        for &dir in Dir6::iter() {
+            let first_step = first_step.unwrap_or(dir);
+            if is_goal(loc, dir) {
+                return Some(first_step);
+            }
+            let next = loc.jump(world, dir);
+            if seen.contains(&next)
+                || world.fov_status(next).is_none()
+                || !world.can_enter_terrain(e, next)
+            {
+                continue;
+            }
+            seen.insert(next);
+            edge.push_back((next, Some(first_step)));
+        }
+    }
+    None
+}
@@ -3,6 +3,8 @@ use calx::Dir6;
 use calx::Incremental;
 use calx_ecs::Entity;
 use serde::{Deserialize, Serialize};
+use std::error::Error;
+use std::fmt;
 
 /// Return type for actions that might fail.
 ///
@@ -22,6 +24,8 @@ pub enum Command {
     Step(Dir6),
     /// Melee attack in direction.
     Melee(Dir6),
+    /// Fire a ranged attack in direction.
+    Shoot(Dir6),
     /// Pick up the topmost item from the floor where you're standing on.
     ///
     /// TODO: Item selection support.
@@ -50,6 +54,63 @@ pub enum Command {
     },
 }
 
+/// Reason why the player can't carry out a command.
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub enum CommandError {
+    /// There is no living player to command.
+    NoPlayer,
+    /// Waiting is only for when the player can't act.
+    WrongTurn,
+    /// The item isn't in the player's inventory.
+    NotCarried,
+    /// There's no item in the slot.
+    EmptySlot,
+    /// There's already an item in the slot.
+    SlotOccupied,
+    /// The item doesn't fit in the slot.
+    WrongSlot,
+    /// There's no room left in the bag.
+    BagFull,
+    /// Cursed equipment can't be taken off.
+    Cursed(String),
+    /// The spell is not known.
+    UnknownSpell,
+    /// Not enough mana to cast the spell.
+    NoMana,
+    /// Equipment needs a higher experience level.
+    LevelTooLow(i32),
+    /// Equipment needs more power.
+    TooWeak(i32),
+    /// Equipment must be held in hands.
+    NoHands,
+    /// Two-handed weapons and shields don't go together.
+    HandsFull,
+}
+
+impl fmt::Display for CommandError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        use CommandError::*;
+        match self {
+            NoPlayer => write!(f, "You are dead."),
+            WrongTurn => write!(f, "It's your turn."),
+            NotCarried => write!(f, "You don't have that."),
+            EmptySlot => write!(f, "There's nothing there."),
+            SlotOccupied => write!(f, "That slot is taken."),
+            WrongSlot => write!(f, "That doesn't go there."),
+            BagFull => write!(f, "Your bag is full."),
+            Cursed(name) => write!(f, "The {} is cursed and won't come off.", name),
+            UnknownSpell => write!(f, "You don't know that spell."),
+            NoMana => write!(f, "Not enough mana."),
+            LevelTooLow(level) => write!(f, "You need to be level {} to use that.", level),
+            TooWeak(power) => write!(f, "You need power {} to use that.", power),
+            NoHands => write!(f, "You need hands to hold that."),
+            HandsFull => write!(f, "Your hands are full."),
+        }
+    }
+}
+
+impl Error for CommandError {}
+
 impl Incremental for World {
     type Seed = WorldSeed;
     type Event = Command;
@@ -72,50 +133,114 @@ impl World {
     /// Return whether a command will work in the current world state.
     ///
     /// Mostly for cases where the feedback is important for the UI (eg. inventory logic).
-    pub fn can_command(&self, cmd: &Command) -> bool {
+    pub fn can_command(&self, cmd: &Command) -> bool { self.check_command(cmd).is_ok() }
+
+    /// Check whether a command will work and explain why if it won't.
+    pub fn check_command(&self, cmd: &Command) -> Result<(), CommandError> {
         use Command::*;
+        use CommandError::*;
 
-        if self.player().is_none() {
-            return *cmd == Command::Wait;
-        }
-        let player = self.player().unwrap();
+        let player = match self.player() {
+            Some(player) => player,
+            None if *cmd == Wait => return Ok(()),
+            None => return Err(NoPlayer),
+        };
 
         match cmd {
-            Wait => !self.player_can_act(),
+            Wait if self.player_can_act() => Err(WrongTurn),
+
+            Drop(slot) => {
+                self.entity_equipped(player, *slot).ok_or(EmptySlot)?;
+                self.check_not_cursed(player, *slot)
+            }
+
+            Equip(slot) => {
+                let item = self.entity_equipped(player, *slot).ok_or(EmptySlot)?;
+                if slot.is_equipment_slot() {
+                    self.check_not_cursed(player, *slot)?;
+                    self.free_bag_slot(player).ok_or(BagFull)?;
+                } else {
+                    if self.equip_type(item).is_none() {
+                        return Err(WrongSlot);
+                    }
+                    self.check_equip(player, item)?;
+                    self.free_equip_slot(player, item).ok_or(SlotOccupied)?;
+                }
+                Ok(())
+            }
 
             InventoryPlace(item, slot) => {
                 if !self.entity_contains(player, *item) {
-                    return false;
+                    return Err(NotCarried);
                 }
                 if self.entity_equipped(player, *slot).is_some() {
-                    return false;
+                    return Err(SlotOccupied);
                 }
                 if !slot.accepts(self.equip_type(*item)) {
-                    return false;
+                    return Err(WrongSlot);
+                }
+                if let Some(old_slot) = self.entity_slot(*item) {
+                    self.check_not_cursed(player, old_slot)?;
+                }
+                if slot.is_equipment_slot() {
+                    self.check_equip(player, *item)?;
                 }
-                true
+                Ok(())
             }
 
             InventorySwap(slot1, slot2) => {
                 if slot1 == slot2 {
-                    return false;
+                    return Err(WrongSlot);
                 }
                 if let Some(e) = self.entity_equipped(player, *slot1) {
                     if !slot2.accepts(self.equip_type(e)) {
-                        return false;
+                        return Err(WrongSlot);
                     }
                 }
-
                 if let Some(e) = self.entity_equipped(player, *slot2) {
                     if !slot1.accepts(self.equip_type(e)) {
-                        return false;
+                        return Err(WrongSlot);
+                    }
+                }
+                self.check_not_cursed(player, *slot1)?;
+                self.check_not_cursed(player, *slot2)?;
+                for &(from, to) in &[(slot1, slot2), (slot2, slot1)] {
+                    if let Some(e) = self.entity_equipped(player, *from) {
+                        if to.is_equipment_slot() && !from.is_equipment_slot() {
+                            self.check_equip(player, e)?;
+                        }
                     }
                 }
-                true
+                Ok(())
+            }
+
+            UntargetedAbility {
+                ability,
+                item: None,
+            }
+            | TargetedAbility {
+                ability,
+                item: None,
+                ..
+            } => {
+                if !self.knows_spell(player, *ability) {
+                    Err(UnknownSpell)
+                } else if !self.can_cast(player, *ability) {
+                    Err(NoMana)
+                } else {
+                    Ok(())
+                }
             }
 
             // TODO: Add failure checks for the rest as needed.
-            _ => true,
+            _ => Ok(()),
+        }
+    }
+
+    fn check_not_cursed(&self, e: Entity, slot: Slot) -> Result<(), CommandError> {
+        match self.cursed_in_slot(e, slot) {
+            Some(item) => Err(CommandError::Cursed(self.entity_name(item))),
+            None => Ok(()),
         }
     }
 
@@ -135,6 +260,10 @@ impl World {
                 let player = self.player()?;
                 self.entity_melee(player, *dir)
             }
+            Shoot(dir) => {
+                let player = self.player()?;
+                self.entity_shoot(player, *dir)
+            }
             Take => {
                 let player = self.player()?;
                 let item = self.item_at(self.location(player)?)?;
@@ -142,6 +271,7 @@ impl World {
             }
             Drop(slot) => {
                 let player = self.player()?;
+                self.check_command(cmd).ok()?;
                 self.place_entity(self.entity_equipped(player, *slot)?, self.location(player)?);
                 // Dropping items does not cost a turn since you'll be doing it from the inventory
                 // screen.
@@ -149,14 +279,13 @@ impl World {
             }
             Equip(slot) => {
                 let player = self.player()?;
+                self.check_command(cmd).ok()?;
                 let item = self.entity_equipped(player, *slot)?;
                 let swap_slot = if slot.is_equipment_slot() {
                     // Remove equipped.
-                    // TODO: Items that can't be removed because of curses etc. trip here.
                     self.free_bag_slot(player)?
                 } else {
                     // Equip from bag.
-                    // TODO: Inability to equip item because stats limits etc. trips here.
                     self.free_equip_slot(player, item)?
                 };
 
@@ -164,7 +293,6 @@ impl World {
                 Some(false)
             }
             InventoryPlace(item, slot) => {
-                // TODO: Check curses, limits etc, as in Equip
                 let player = self.player()?;
 
                 // Checks implemented in can_command, piggyback on those.
@@ -176,7 +304,6 @@ impl World {
                 Some(false)
             }
             InventorySwap(slot1, slot2) => {
-                // TODO: Check curses, limits etc, as in Equip
                 let player = self.player()?;
 
                 // Checks implemented in can_command, piggyback on those.
@@ -225,3 +352,126 @@ impl World {
         }
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::{Command, CommandError};
+    use crate::{ExternalEntity, Slot, World, WorldSeed, WorldSkeleton};
+    use calx::Incremental;
+
+    #[test]
+    fn test_cursed_equipment() {
+        let mut world = World::from_seed(&WorldSeed {
+            rng_seed: 1,
+            world_skeleton: WorldSkeleton::dungeon_dive(),
+            player_character: ExternalEntity::from_name("player").unwrap(),
+        });
+        let player = world.player().unwrap();
+        let loc = world.location(player).unwrap();
+        while !world.player_can_act() {
+            world.update(&Command::Wait);
+        }
+
+        let mut helmet = ExternalEntity::from_name("helmet").unwrap();
+        helmet.loadout.item.as_mut().unwrap().cursed = true;
+        let helmet = world.spawn(&helmet, loc);
+        world.equip_item(helmet, player, Slot::Bag(0));
+
+        // Putting it on works.
+        assert_eq!(world.check_command(&Command::Equip(Slot::Bag(0))), Ok(()));
+        world.update(&Command::Equip(Slot::Bag(0)));
+        assert_eq!(world.entity_equipped(player, Slot::Head), Some(helmet));
+
+        // Taking it off doesn't.
+        for cmd in &[
+            Command::Equip(Slot::Head),
+            Command::Drop(Slot::Head),
+            Command::InventorySwap(Slot::Head, Slot::Bag(1)),
+            Command::InventoryPlace(helmet, Slot::Bag(1)),
+        ] {
+            assert_eq!(
+                world.check_command(cmd),
+                Err(CommandError::Cursed("helmet".into()))
+            );
+            assert!(!world.can_command(cmd));
+        }
+        while !world.player_can_act() {
+            world.update(&Command::Wait);
+        }
+        world.update(&Command::Equip(Slot::Head));
+        assert_eq!(world.entity_equipped(player, Slot::Head), Some(helmet));
+    }
+
+    #[test]
+    fn test_equipment_requirements() {
+        let mut world = World::from_seed(&WorldSeed {
+            rng_seed: 1,
+            world_skeleton: WorldSkeleton::dungeon_dive(),
+            player_character: ExternalEntity::from_name("player").unwrap(),
+        });
+        let player = world.player().unwrap();
+        let loc = world.location(player).unwrap();
+        while !world.player_can_act() {
+            world.update(&Command::Wait);
+        }
+
+        let mut give = |name: &str, slot| {
+            let mut item = ExternalEntity::from_name(name).unwrap();
+            item.loadout.item.as_mut().unwrap().cursed = false;
+            let item = world.spawn(&item, loc);
+            world.equip_item(item, player, slot);
+            item
+        };
+        let greatsword = give("greatsword", Slot::Bag(0));
+        let shield = give("shield", Slot::Bag(1));
+
+        assert!(world
+            .item_description(greatsword)
+            .contains("requires level 3, both hands"));
+        assert_eq!(
+            world.check_command(&Command::Equip(Slot::Bag(0))),
+            Err(CommandError::LevelTooLow(3))
+        );
+        assert_eq!(
+            world.check_command(&Command::InventoryPlace(greatsword, Slot::RightHand)),
+            Err(CommandError::LevelTooLow(3))
+        );
+
+        world.ecs_mut().stats[player].base.level = 3;
+        world.rebuild_stats(player);
+
+        // Can't wield a two-handed weapon with a shield.
+        world.update(&Command::Equip(Slot::Bag(1)));
+        assert!(world.entity_equipped(player, Slot::LeftHand).is_some());
+        assert_eq!(
+            world.check_command(&Command::Equip(Slot::Bag(0))),
+            Err(CommandError::HandsFull)
+        );
+        world.update(&Command::Equip(Slot::Bag(0)));
+        assert_eq!(world.entity_equipped(player, Slot::RightHand), None);
+
+        // Or the other way around.
+        world.update(&Command::Equip(Slot::LeftHand));
+        world.update(&Command::Equip(Slot::Bag(0)));
+        assert_eq!(
+            world.entity_equipped(player, Slot::RightHand),
+            Some(greatsword)
+        );
+        assert_eq!(
+            world.check_command(&Command::Equip(Slot::Bag(1))),
+            Err(CommandError::HandsFull)
+        );
+        assert_eq!(
+            world.check_command(&Command::InventoryPlace(shield, Slot::LeftHand)),
+            Err(CommandError::HandsFull)
+        );
+
+        // Level drain makes the player put down the weapon.
+        world.gain_xp(player, -1);
+        assert_eq!(world.level(player), 2);
+        assert_eq!(world.entity_equipped(player, Slot::RightHand), None);
+        assert!(world
+            .entities_in_bag(player)
+            .contains(&(Slot::Bag(0), greatsword)));
+    }
+}
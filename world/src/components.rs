@@ -4,7 +4,7 @@ use crate::FovStatus;
 use serde::{Deserialize, Serialize};
 
 /// Map field-of-view and remembered terrain.
-#[derive(Clone, Debug, Eq, PartialEq, Default, Serialize, Deserialize)]
+#[derive(Clone, Debug, Eq, PartialEq, Hash, Default, Serialize, Deserialize)]
 pub struct MapMemory {
     pub seen: LocationSet,
     pub remembered: LocationSet,
@@ -0,0 +1,222 @@
+//! Things that happen when mobs die
+
+use crate::{
+    effect::{Damage, Effect},
+    msg,
+    noise::EXPLOSION_LOUDNESS,
+    stats::{Health, Intrinsic},
+    volume::Volume,
+    world::Loadout,
+    AnimState, Distribution, ExternalEntity, Location, LootTable, Terrain, World,
+};
+use calx::Dir6;
+use calx_ecs::Entity;
+use serde::{Deserialize, Serialize};
+
+/// Effect triggered by the death of a mob.
+#[derive(Clone, Eq, PartialEq, Hash, Debug, Serialize, Deserialize)]
+pub enum DeathEffect {
+    /// Blow up and hurt everything around the corpse.
+    Explode {
+        radius: u32,
+        amount: u32,
+        damage: Damage,
+    },
+    /// Split into weaker copies of self. Mobs too weak to divide further just die.
+    Split { count: u32 },
+    /// Leave behind a named item.
+    Drop(String),
+    /// Change the terrain where the mob died.
+    Terrain(Terrain),
+    /// Roll a loot table for an item to leave behind.
+    Loot(LootTable),
+}
+
+/// Death effects of an entity, in the order they happen.
+pub type DeathEffects = Vec<DeathEffect>;
+
+impl World {
+    /// Return the effects that happen when the entity dies.
+    ///
+    /// Includes the effects from intrinsics along with the ones given in the entity's spec.
+    pub fn death_effects(&self, e: Entity) -> DeathEffects {
+        let mut ret = self.ecs().on_death.get(e).cloned().unwrap_or_default();
+
+        let explodes = ret.iter().any(|d| matches!(d, DeathEffect::Explode { .. }));
+        if self.has_intrinsic(e, Intrinsic::Deathsplosion) && !explodes {
+            ret.push(DeathEffect::Explode {
+                radius: 1,
+                amount: self.power(e).max(1) as u32,
+                damage: Damage::Fire,
+            });
+        }
+
+        ret
+    }
+
+    /// Run the death effects of an entity that died at the given location.
+    ///
+    /// Must be called after the entity has been removed from the map, so that it won't get caught
+    /// in its own death throes.
+    pub(crate) fn run_death_effects(&mut self, e: Entity, loc: Location) {
+        for effect in self.death_effects(e) {
+            match effect {
+                DeathEffect::Explode {
+                    radius,
+                    amount,
+                    damage,
+                } => {
+                    if self.player_sees(loc) {
+                        msg!("[One] explode[s]!"; self.subject(e));
+                    }
+                    let volume = Volume::sphere(self, loc, radius);
+                    for &pt in &volume.0 {
+                        self.spawn_fx(pt, AnimState::Explosion);
+                    }
+                    self.apply_effect(&Effect::Hit { amount, damage }, &volume, None);
+                    self.make_noise(loc, EXPLOSION_LOUDNESS, None);
+                }
+                DeathEffect::Split { count } => self.split(e, loc, count),
+                DeathEffect::Drop(name) => {
+                    if let Ok(item) = ExternalEntity::from_name(&name) {
+                        self.spawn(&item, loc);
+                    }
+                }
+                DeathEffect::Terrain(terrain) => self.set_terrain(loc, terrain),
+                DeathEffect::Loot(table) => {
+                    // One-off RNG like with worldgen spawns, so drops don't perturb the world RNG.
+                    let mut rng = calx::seeded_rng(&(self.rng_seed(), loc, self.get_tick()));
+                    if let Some(spawn) = table.roll(&mut rng, self.depth(loc)) {
+                        let item = spawn.sample(&mut rng);
+                        let drop_loc = self.empty_item_drop_location(loc);
+                        self.spawn(&item, drop_loc);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Spawn weaker copies of a dead mob around its location.
+    fn split(&mut self, e: Entity, loc: Location, count: u32) {
+        let power = self.base_stats(e).base_power / 2;
+        if power < 1 {
+            return;
+        }
+
+        let mut loadout = Loadout::get(self.ecs(), e);
+        if let Some(stats) = loadout.stats.as_mut() {
+            stats.base.base_power = power;
+            stats.base.base_attack = power;
+        }
+        loadout.health = Some(Health::default());
+        loadout.status = Some(Default::default());
+        loadout.anim = Some(Default::default());
+        let spawn = ExternalEntity::new(loadout);
+
+        let mut spots = Vec::new();
+        for &dir in Dir6::iter() {
+            let spot = loc.jump(self, dir);
+            if spots.len() < count as usize && !self.blocks_walk(spot) {
+                spots.push(spot);
+            }
+        }
+        if spots.is_empty() {
+            return;
+        }
+
+        if self.player_sees(loc) {
+            msg!("[One] split[s] apart!"; self.subject(e));
+        }
+        for spot in spots {
+            self.spawn(&spawn, spot);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        effect::Damage, DeathEffect, ExternalEntity, Location, LootTable, Terrain, World,
+        WorldSeed, WorldSkeleton,
+    };
+    use calx::{Dir6, Incremental};
+
+    fn spawn(world: &mut World, name: &str, loc: Location) -> calx_ecs::Entity {
+        world.spawn(&ExternalEntity::from_name(name).unwrap(), loc)
+    }
+
+    #[test]
+    fn test_death_effects() {
+        let mut world = World::from_seed(&WorldSeed {
+            rng_seed: 1,
+            world_skeleton: WorldSkeleton::dungeon_dive(),
+            player_character: ExternalEntity::from_name("player").unwrap(),
+        });
+        let player = world.player().unwrap();
+        let origin = world.location(player).unwrap();
+
+        // Find an open spot away from other mobs.
+        let loc = world
+            .noise_area(origin, 30)
+            .into_iter()
+            .find(|&loc| {
+                Dir6::iter().all(|&dir| !world.terrain_blocks_walk(loc.jump(&world, dir)))
+                    && world
+                        .noise_area(loc, 4)
+                        .iter()
+                        .all(|&x| world.mob_at(x).is_none())
+            })
+            .expect("No room for test");
+        let next = loc.jump(&world, Dir6::North);
+
+        // Exploding mobs hurt their neighbors.
+        let eye = spawn(&mut world, "eye horror", loc);
+        let dreg = spawn(&mut world, "dreg", next);
+        // Don't let a loot roll leave items around to confuse the drop checks later.
+        world.ecs_mut().on_death.insert(dreg, Vec::new());
+        world.damage(eye, 1000, Damage::Physical, None);
+        assert!(!world.is_alive(eye));
+        assert!(!world.is_alive(dreg));
+
+        // Oozes split into smaller oozes.
+        let ooze = spawn(&mut world, "ooze", loc);
+        world.damage(ooze, 1000, Damage::Physical, None);
+        let spawns: Vec<_> = Dir6::iter()
+            .filter_map(|&dir| world.mob_at(loc.jump(&world, dir)))
+            .collect();
+        assert_eq!(spawns.len(), 2);
+        assert!(spawns.iter().all(|&e| world.power(e) < world.power(ooze)));
+
+        // Small ones die for good.
+        for e in spawns {
+            world.damage(e, 1000, Damage::Physical, None);
+        }
+        assert!(Dir6::iter().all(|&dir| world.mob_at(loc.jump(&world, dir)).is_none()));
+
+        // Constructs leave their armor.
+        let construct = spawn(&mut world, "armored construct", loc);
+        world.damage(construct, 1000, Damage::Physical, None);
+        assert!(world.item_at(loc).is_some());
+
+        // Efreet leave a pool of magma that persists over save and load.
+        let efreet = spawn(&mut world, "efreeti", next);
+        world.damage(efreet, 1000, Damage::Physical, None);
+        assert_eq!(world.terrain(next), Terrain::Magma);
+        let saved = ron::ser::to_string(&world).unwrap();
+        let loaded: World = ron::de::from_str(&saved).unwrap();
+        assert_eq!(loaded.terrain(next), Terrain::Magma);
+
+        // Loot gets dropped next to the armor that's already on the floor.
+        let dreg = spawn(&mut world, "dreg", loc);
+        let loot = LootTable::new(100, &[("sword", 1)]);
+        world
+            .ecs_mut()
+            .on_death
+            .insert(dreg, vec![DeathEffect::Loot(loot)]);
+        world.damage(dreg, 1000, Damage::Physical, None);
+        let drops: Vec<_> = Dir6::iter()
+            .filter_map(|&dir| world.item_at(loc.jump(&world, dir)))
+            .collect();
+        assert_eq!(drops.len(), 1);
+    }
+}
@@ -49,10 +49,13 @@ pub enum Icon {
     Wand1,
     Wand2,
     Scroll1,
+    Bow,
+    Arrow,
+    Shield,
 }
 
 /// Entity name and appearance.
-#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+#[derive(Clone, Debug, Eq, PartialEq, Hash, Serialize, Deserialize)]
 pub struct Desc {
     pub singular_name: String,
     pub plural_name: Option<String>,
@@ -112,6 +115,7 @@ impl World {
 
     pub fn entity_name(&self, e: Entity) -> String {
         if let Some(desc) = self.ecs().desc.get(e) {
+            let desc = self.unknown_appearance(e).unwrap_or_else(|| desc.clone());
             let count = self.count(e);
 
             if count > 1 {
@@ -1,3 +1,4 @@
+use crate::{spec::ABILITIES, AnimState};
 use serde::{Deserialize, Serialize};
 
 /// Game system effects on entities.
@@ -7,33 +8,171 @@ pub enum Effect {
     Hit { amount: u32, damage: Damage },
     /// Cause erratic behavior for a time.
     Confuse,
+    /// Deal damage over time.
+    Poison,
+    /// Heal over time.
+    Regenerate,
+    /// Put to sleep until hurt.
+    Sleep,
+    /// Shrink sight to adjacent cells for a time.
+    Blind,
+    /// Make run away from enemies for a time.
+    Frighten,
+    /// Call up a named creature next to the location as a companion of the source.
+    Summon(&'static str),
 }
 
-#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug, Serialize, Deserialize)]
 pub enum Damage {
     Physical,
     Fire,
     Electricity,
 }
 
+impl Damage {
+    /// Name of the damage type for messages.
+    pub fn noun(self) -> &'static str {
+        match self {
+            Damage::Physical => "blow",
+            Damage::Fire => "fire",
+            Damage::Electricity => "lightning",
+        }
+    }
+}
+
+/// Which locations an ability affects.
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub enum Targeting {
+    /// The user's own location.
+    Caster,
+    /// A random other mob within range of the user.
+    Random,
+    /// The first mob or wall along a line.
+    Bolt,
+    /// An area around where a bolt stops.
+    Ball { radius: u32 },
+    /// A widening area spreading from the user towards the direction.
+    Cone,
+    /// Every location along a line until a wall.
+    Beam,
+}
+
+impl Targeting {
+    /// Return whether abilities with this targeting need a direction.
+    pub fn is_directed(self) -> bool {
+        use Targeting::*;
+        match self {
+            Caster | Random => false,
+            Bolt | Ball { .. } | Cone | Beam => true,
+        }
+    }
+}
+
+/// Description of what an ability does.
+#[derive(Clone, Debug)]
+pub struct AbilitySpec {
+    pub targeting: Targeting,
+    /// Maximum reach of the ability.
+    pub range: u32,
+    /// Effects applied to every affected location.
+    pub effects: Vec<Effect>,
+    /// Animation that flies from the user to the target.
+    pub projectile: Option<AnimState>,
+    /// Animation shown at every affected location.
+    pub impact: Option<AnimState>,
+    /// How loud the ability is at the target, zero for silent.
+    pub noise: u32,
+    /// Message shown when the ability is used.
+    pub message: Option<&'static str>,
+    /// Mana needed to cast the ability as a spell.
+    pub mana_cost: i32,
+}
+
+impl Default for AbilitySpec {
+    fn default() -> Self {
+        AbilitySpec {
+            targeting: Targeting::Caster,
+            range: 0,
+            effects: Vec::new(),
+            projectile: None,
+            impact: None,
+            noise: 0,
+            message: None,
+            mana_cost: 0,
+        }
+    }
+}
+
 /// Actions a being can do
-#[derive(Copy, Clone, Eq, PartialEq, Debug, Serialize, Deserialize)]
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug, Serialize, Deserialize)]
 pub enum Ability {
     // --- Untargeted ---
     LightningBolt,
+    /// Call up a companion to fight alongside the user.
+    Summon,
     // MagicMap
 
     // --- Targeted ---
     Fireball,
     Confuse,
+    /// Burn everything in front of the user.
+    FlameJet,
+    /// Electrocute everything along a line.
+    LightningBeam,
+
+    // --- Mixed, new abilities go at the end to keep saved spell flags stable ---
+    Sleep,
+    Poison,
+    Blind,
+    Terrify,
+    /// Heal the user over time.
+    Regenerate,
 }
 
 impl Ability {
-    pub fn is_targeted(self) -> bool {
+    pub fn iter() -> impl Iterator<Item = &'static Ability> {
         use Ability::*;
-        match self {
-            LightningBolt => false,
-            _ => true,
+        const ABILITIES: [Ability; 11] = [
+            LightningBolt,
+            Summon,
+            Fireball,
+            Confuse,
+            FlameJet,
+            LightningBeam,
+            Sleep,
+            Poison,
+            Blind,
+            Terrify,
+            Regenerate,
+        ];
+        ABILITIES.iter()
+    }
+
+    /// Return the description of what the ability does.
+    pub fn spec(self) -> &'static AbilitySpec {
+        ABILITIES
+            .get(&self)
+            .unwrap_or_else(|| panic!("No spec for ability {:?}", self))
+    }
+
+    pub fn is_targeted(self) -> bool { self.spec().targeting.is_directed() }
+
+    /// Mana needed to cast the ability as a spell.
+    pub fn mana_cost(self) -> i32 { self.spec().mana_cost }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{Ability, Targeting};
+
+    #[test]
+    fn test_ability_specs() {
+        for &a in Ability::iter() {
+            let spec = a.spec();
+            assert!(!spec.effects.is_empty(), "{:?} does nothing", a);
+            if spec.targeting != Targeting::Caster {
+                assert!(spec.range > 0, "{:?} has no range", a);
+            }
         }
     }
 }
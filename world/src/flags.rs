@@ -11,4 +11,6 @@ pub struct Flags {
     /// Store the player entity here for fast access.
     pub player: Option<Entity>,
     pub depth: i32,
+    /// Number of mobs killed by the player.
+    pub kills: u32,
 }
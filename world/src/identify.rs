@@ -0,0 +1,216 @@
+//! Item identification and the random appearances of unknown items
+
+use crate::{desc::Desc, item::ItemType, msg, spec, Icon, World};
+use calx_ecs::Entity;
+use lazy_static::lazy_static;
+use rand::seq::SliceRandom;
+use std::cell::RefCell;
+use std::collections::HashMap;
+
+const WAND_MATERIALS: &[&str] = &[
+    "oak", "bone", "iron", "glass", "copper", "ivory", "ebony", "crystal", "silver", "jade",
+    "brass", "willow", "coral", "obsidian",
+];
+
+const SCROLL_LABELS: &[&str] = &[
+    "ASH KORU",
+    "VOLMIR TEZ",
+    "OOLA BAN",
+    "KESTRA DUN",
+    "MYRR ETH",
+    "ZOTH OMM",
+    "ULDA VEX",
+    "NIRIM SAAL",
+    "TAVO PEL",
+    "GRISH NAK",
+    "EBRO LUN",
+    "QUAZ ITH",
+];
+
+/// What an unidentified item looks like, decided by the item's icon.
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+enum Appearance {
+    Wand,
+    Scroll,
+}
+
+impl Appearance {
+    fn from_icon(icon: Icon) -> Option<Appearance> {
+        match icon {
+            Icon::Wand1 | Icon::Wand2 => Some(Appearance::Wand),
+            Icon::Scroll1 => Some(Appearance::Scroll),
+            _ => None,
+        }
+    }
+
+    fn labels(self) -> &'static [&'static str] {
+        match self {
+            Appearance::Wand => WAND_MATERIALS,
+            Appearance::Scroll => SCROLL_LABELS,
+        }
+    }
+
+    fn desc(self, label: &str, icon: Icon) -> Desc {
+        let name = match self {
+            Appearance::Wand => format!("{} wand|{} wands", label, label),
+            Appearance::Scroll => format!("scroll labeled {}|scrolls labeled {}", label, label),
+        };
+        Desc::new(&name, icon)
+    }
+}
+
+lazy_static! {
+    /// Spawn names, appearances and icons of the usable item kinds that start out unidentified.
+    ///
+    /// The spec database is ordered, so the list is in a stable order.
+    static ref UNKNOWN_KINDS: Vec<(String, Appearance, Icon)> = {
+        let mut ret = Vec::new();
+        for spec in spec::iter_specs() {
+            let e = spec.sample(&mut calx::seeded_rng(&1));
+            let usable = matches!(
+                e.loadout.item.map(|i| i.item_type),
+                Some(ItemType::TargetedUsable(_)) | Some(ItemType::UntargetedUsable(_))
+            );
+            let icon = e.loadout.desc.map(|d| d.icon);
+            if let (true, Some(icon)) = (usable, icon) {
+                if let Some(appearance) = Appearance::from_icon(icon) {
+                    ret.push((spec.id().to_string(), appearance, icon));
+                }
+            }
+        }
+        for &appearance in &[Appearance::Wand, Appearance::Scroll] {
+            let count = ret.iter().filter(|(_, a, _)| *a == appearance).count();
+            assert!(
+                count <= appearance.labels().len(),
+                "Not enough {:?} appearances",
+                appearance
+            );
+        }
+        ret
+    };
+}
+
+/// Unidentified descriptions of item kinds for the current game, built on first use.
+///
+/// The appearances are derived from the world seed, so they're not saved.
+#[derive(Default)]
+pub(crate) struct AppearanceCache {
+    descs: RefCell<Option<HashMap<String, Desc>>>,
+}
+
+impl World {
+    /// Return whether the player knows what kind of item this is.
+    ///
+    /// Items that have no unknown appearance are always identified.
+    pub fn is_identified(&self, item: Entity) -> bool { self.unknown_appearance(item).is_none() }
+
+    /// Return the description an unidentified item shows instead of its real one.
+    pub(crate) fn unknown_appearance(&self, item: Entity) -> Option<Desc> {
+        let name = self.spawn_name(item)?;
+        if self.identified.contains(name) {
+            return None;
+        }
+        let mut descs = self.appearances.descs.borrow_mut();
+        let descs = descs.get_or_insert_with(|| self.build_appearances());
+        descs.get(name).cloned()
+    }
+
+    fn build_appearances(&self) -> HashMap<String, Desc> {
+        // Appearances are shuffled differently in every game.
+        let mut rng = calx::seeded_rng(&(self.rng_seed(), "appearances"));
+        let mut ret = HashMap::new();
+        for &appearance in &[Appearance::Wand, Appearance::Scroll] {
+            let mut labels = appearance.labels().to_vec();
+            labels.shuffle(&mut rng);
+            let kinds = UNKNOWN_KINDS.iter().filter(|(_, a, _)| *a == appearance);
+            for ((name, _, icon), label) in kinds.zip(labels) {
+                ret.insert(name.clone(), appearance.desc(label, *icon));
+            }
+        }
+        ret
+    }
+
+    /// Make the player recognize all items of the same kind as this one from now on.
+    pub(crate) fn identify(&mut self, item: Entity) {
+        if self.is_identified(item) {
+            return;
+        }
+        let old_name = self.subject(item);
+        if let Some(name) = self.spawn_name(item).map(|n| n.to_string()) {
+            self.identified.insert(name);
+        }
+        msg!("[One] [is] [a thing]."; old_name, self.object(item));
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{Ability, Command, ExternalEntity, World, WorldSeed, WorldSkeleton};
+    use calx::Incremental;
+
+    fn new_world(rng_seed: u32) -> World {
+        World::from_seed(&WorldSeed {
+            rng_seed,
+            world_skeleton: WorldSkeleton::dungeon_dive(),
+            player_character: ExternalEntity::from_name("player").unwrap(),
+        })
+    }
+
+    #[test]
+    fn test_identification() {
+        let mut world = new_world(1);
+        let player = world.player().unwrap();
+        let loc = world.location(player).unwrap();
+
+        let spawn = |world: &mut World, name| {
+            let e = world.spawn(&ExternalEntity::from_name(name).unwrap(), loc);
+            world.equip_item(e, player, world.free_bag_slot(player).unwrap());
+            e
+        };
+
+        let wand = spawn(&mut world, "wand of confusion");
+        let scroll = spawn(&mut world, "scroll of regeneration");
+        let sword = spawn(&mut world, "sword");
+        assert!(!world.is_identified(wand));
+        assert!(!world.is_identified(scroll));
+        assert!(world.is_identified(sword));
+        assert!(world.entity_name(wand).ends_with(" wand"));
+        assert!(world.entity_name(scroll).starts_with("scroll labeled "));
+        assert_eq!(world.entity_name(sword), "sword");
+
+        // Targeted scrolls still look like scrolls.
+        let fear = spawn(&mut world, "scroll of fear");
+        assert!(world.entity_name(fear).starts_with("scroll labeled "));
+
+        // Other games have different appearances.
+        let names: Vec<String> = (1..10)
+            .map(|seed| {
+                let mut w = new_world(seed);
+                let e = w.spawn(
+                    &ExternalEntity::from_name("wand of confusion").unwrap(),
+                    loc,
+                );
+                w.entity_name(e)
+            })
+            .collect();
+        assert!(names.iter().any(|n| n != &names[0]));
+
+        // Using an item identifies every item of its kind.
+        let second_scroll = spawn(&mut world, "scroll of regeneration");
+        while !world.player_can_act() {
+            world.update(&Command::Wait);
+        }
+        world.update(&Command::UntargetedAbility {
+            ability: Ability::Regenerate,
+            item: Some(scroll),
+        });
+        assert!(world.is_identified(second_scroll));
+        assert_eq!(world.entity_name(second_scroll), "scroll of regeneration");
+        assert!(!world.is_identified(wand));
+
+        // Identification persists over save and load.
+        let saved = ron::ser::to_string(&world).unwrap();
+        let loaded: World = ron::de::from_str(&saved).unwrap();
+        assert!(loaded.is_identified(second_scroll));
+    }
+}
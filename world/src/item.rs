@@ -1,17 +1,18 @@
 //! Item and equipment logic
 
-use crate::{msg, Ability, ActionOutcome, Location, World};
+use crate::{msg, stats::Intrinsic, Ability, ActionOutcome, CommandError, Location, World};
 use calx::{hex_neighbors, CellVector, HexGeom};
 use calx_ecs::Entity;
 use euclid::vec2;
 use serde::{Deserialize, Serialize};
 use std::collections::{HashSet, VecDeque};
+use std::fmt;
 use std::slice;
 
 pub const BAG_CAPACITY: u32 = 50;
 
 /// Inventory slots.
-#[derive(Copy, Eq, PartialEq, Clone, Debug, PartialOrd, Ord, Serialize, Deserialize)]
+#[derive(Copy, Eq, PartialEq, Hash, Clone, Debug, PartialOrd, Ord, Serialize, Deserialize)]
 pub enum Slot {
     Bag(u32),
     Head,
@@ -37,7 +38,7 @@ impl Slot {
         use self::Slot::*;
         match self {
             RightHand => equip_type == Some(EquipType::Melee),
-            LeftHand => false, // TODO: Shields etc
+            LeftHand => equip_type == Some(EquipType::Shield),
             Ranged => equip_type == Some(EquipType::Ranged),
             Head => equip_type == Some(EquipType::Head),
             Body => equip_type == Some(EquipType::Body),
@@ -57,7 +58,7 @@ impl Slot {
     }
 }
 
-#[derive(Copy, Eq, PartialEq, Clone, Debug, Serialize, Deserialize)]
+#[derive(Copy, Eq, PartialEq, Hash, Clone, Debug, Serialize, Deserialize)]
 pub enum ItemType {
     MeleeWeapon,
     RangedWeapon,
@@ -73,6 +74,10 @@ pub enum ItemType {
     TargetedUsable(Ability),
     /// Consumed instantly when stepped on.
     Instant(Ability),
+    /// Shot with a ranged weapon, carried in the bag.
+    Ammo,
+    /// Held in the off hand.
+    Shield,
 }
 
 #[derive(Copy, Eq, PartialEq, Clone, Debug, Serialize, Deserialize)]
@@ -83,18 +88,59 @@ pub enum EquipType {
     Body,
     Feet,
     Trinket,
+    Shield,
+}
+
+/// What it takes to use a piece of equipment.
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq, Hash, Serialize, Deserialize)]
+pub struct Requirements {
+    /// Minimum experience level of the wearer.
+    pub level: i32,
+    /// Minimum power of the wearer.
+    pub power: i32,
+    /// Wearer must have hands to hold the item.
+    pub hands: bool,
+    /// Weapon is held in both hands.
+    ///
+    /// The weapon itself goes in the right hand slot, and the left hand slot can't hold anything
+    /// while it's wielded.
+    pub two_handed: bool,
+}
+
+impl fmt::Display for Requirements {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut parts = Vec::new();
+        if self.level > 0 {
+            parts.push(format!("level {}", self.level));
+        }
+        if self.power > 0 {
+            parts.push(format!("power {}", self.power));
+        }
+        if self.two_handed {
+            parts.push("both hands".to_string());
+        } else if self.hands {
+            parts.push("hands".to_string());
+        }
+        write!(f, "{}", parts.join(", "))
+    }
 }
 
 /// Items can be picked up and carried and they do stuff.
-#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+#[derive(Clone, Debug, Eq, PartialEq, Hash, Serialize, Deserialize)]
 pub struct Item {
     pub item_type: ItemType,
     /// How many uses a wand or similar has left.
     pub charges: u32,
+    /// Cursed equipment can't be taken off once worn.
+    #[serde(default)]
+    pub cursed: bool,
+    /// What it takes to equip the item.
+    #[serde(default)]
+    pub requirements: Requirements,
 }
 
 /// An entity that can become a stack of multiple copies.
-#[derive(Copy, Clone, Eq, PartialEq, Debug, Serialize, Deserialize)]
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug, Serialize, Deserialize)]
 pub struct Stacking {
     pub count: u32,
 }
@@ -106,6 +152,94 @@ impl Default for Stacking {
 impl World {
     pub fn is_item(&self, e: Entity) -> bool { self.ecs().item.contains(e) }
 
+    pub fn is_cursed(&self, item: Entity) -> bool {
+        self.ecs().item.get(item).is_some_and(|i| i.cursed)
+    }
+
+    pub fn requirements(&self, item: Entity) -> Requirements {
+        self.ecs()
+            .item
+            .get(item)
+            .map(|i| i.requirements)
+            .unwrap_or_default()
+    }
+
+    /// Check whether a mob is able to equip an item.
+    ///
+    /// Only checks the wearer and the other equipment, not whether the item is at hand. A wielded
+    /// two-handed weapon counts as occupying the left hand slot.
+    pub fn check_equip(&self, e: Entity, item: Entity) -> Result<(), CommandError> {
+        use CommandError::*;
+        let req = self.requirements(item);
+        if self.level(e) < req.level {
+            return Err(LevelTooLow(req.level));
+        }
+        if self.power(e) < req.power {
+            return Err(TooWeak(req.power));
+        }
+        if (req.hands || req.two_handed) && !self.has_intrinsic(e, Intrinsic::Hands) {
+            return Err(NoHands);
+        }
+        if req.two_handed && self.entity_equipped(e, Slot::LeftHand).is_some() {
+            return Err(HandsFull);
+        }
+        if self.equip_type(item) == Some(EquipType::Shield) {
+            if let Some(weapon) = self.entity_equipped(e, Slot::RightHand) {
+                if self.requirements(weapon).two_handed {
+                    return Err(HandsFull);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Move worn items whose requirements the mob no longer meets, eg. after level drain, back to
+    /// the bag.
+    ///
+    /// Items go on the floor if the bag is full. Cursed items stay on no matter what.
+    pub(crate) fn unequip_unusable(&mut self, e: Entity) {
+        for &slot in Slot::equipment_iter() {
+            let item = match self.entity_equipped(e, slot) {
+                Some(item) => item,
+                None => continue,
+            };
+            if self.is_cursed(item) || self.check_equip(e, item).is_ok() {
+                continue;
+            }
+            msg!("[One] can't use [another] any more."; self.subject(e), self.object(item));
+            if let Some(bag_slot) = self.free_bag_slot(e) {
+                self.equip_item(item, e, bag_slot);
+            } else if let Some(loc) = self.location(e) {
+                self.place_entity(item, loc);
+            }
+        }
+        self.rebuild_stats(e);
+    }
+
+    /// Return a line of text describing an item and whether the player can equip it.
+    pub fn item_description(&self, item: Entity) -> String {
+        let mut ret = self.entity_name(item);
+        let req = self.requirements(item);
+        if req != Requirements::default() {
+            ret += &format!(" (requires {})", req);
+        }
+        if let (Some(player), Some(_)) = (self.player(), self.equip_type(item)) {
+            if let Err(err) = self.check_equip(player, item) {
+                ret += &format!(" - {}", err);
+            }
+        }
+        ret
+    }
+
+    /// Return the cursed item stuck in an equipment slot, if any.
+    pub fn cursed_in_slot(&self, e: Entity, slot: Slot) -> Option<Entity> {
+        if !slot.is_equipment_slot() {
+            return None;
+        }
+        self.entity_equipped(e, slot)
+            .filter(|&item| self.is_cursed(item))
+    }
+
     pub fn entities_in_bag(&self, parent: Entity) -> Vec<(Slot, Entity)> {
         self.entities_in(parent)
             .into_iter()
@@ -279,6 +413,14 @@ impl World {
         self.ecs().item.get(e).and_then(|item| Some(item.item_type))
     }
 
+    /// Return the ammunition stack an entity would use for its ranged weapon.
+    pub fn ammo(&self, e: Entity) -> Option<Entity> {
+        self.entities_in_bag(e)
+            .into_iter()
+            .map(|(_, item)| item)
+            .find(|&item| self.item_type(item) == Some(ItemType::Ammo))
+    }
+
     pub fn free_bag_slot(&self, e: Entity) -> Option<Slot> {
         (0..BAG_CAPACITY)
             .find(|&i| self.entity_equipped(e, Slot::Bag(i)).is_none())
@@ -319,6 +461,7 @@ impl World {
             Some(Armor) => Some(EquipType::Body),
             Some(Boots) => Some(EquipType::Feet),
             Some(Trinket) => Some(EquipType::Trinket),
+            Some(Shield) => Some(EquipType::Shield),
             _ => None,
         }
     }
@@ -5,11 +5,17 @@ mod ai;
 mod animations;
 pub use animations::{Anim, AnimState, LerpLocation, PhysicsSpace, PhysicsVector};
 
+mod bot;
+pub use bot::{Aggressor, Bot, Explorer, RandomWalker};
+
 mod command;
-pub use command::{ActionOutcome, Command};
+pub use command::{ActionOutcome, Command, CommandError};
 
 mod components;
 
+mod death;
+pub use death::DeathEffect;
+
 mod desc;
 pub use desc::Icon;
 
@@ -25,6 +31,8 @@ mod fov;
 
 mod grammar;
 
+mod identify;
+
 mod item;
 pub use item::{ItemType, Slot};
 
@@ -33,13 +41,22 @@ pub use location::{Location, Portal};
 
 mod location_set;
 
+mod loot;
+pub use loot::LootTable;
+
 mod mapsave;
-pub use mapsave::WorldData;
+pub use mapsave::{MapPatch, WorldData};
 
 mod map;
 
+mod migrate;
+
 mod movement;
 
+mod noise;
+
+mod pathing;
+
 mod msg;
 pub use msg::{register_msg_receiver, MsgReceiver};
 
@@ -47,12 +64,29 @@ mod mutate;
 
 mod query;
 
+mod replay;
+pub use replay::{Checkpoint, Replay, ReplayError};
+
+mod runner;
+pub use runner::{Outcome, Runner, TurnReport};
+
+mod savegame;
+pub use savegame::{SaveError, SaveManager, SaveSlot, THUMBNAIL_HEIGHT, THUMBNAIL_WIDTH};
+
 mod sector;
-pub use sector::{Sector, SectorDir, SectorVec, WorldSkeleton, SECTOR_HEIGHT, SECTOR_WIDTH};
+pub use sector::{
+    Biome, Connection, Sector, SectorDir, SectorMap, SectorSpec, SectorVec, WorldSkeleton,
+    SECTOR_HEIGHT, SECTOR_WIDTH,
+};
+
+mod skeleton;
+pub use skeleton::{DungeonData, SkeletonData};
 
 mod spatial;
 mod spec;
+mod state_hash;
 mod stats;
+pub use stats::Status;
 
 pub mod terrain;
 pub use terrain::Terrain;
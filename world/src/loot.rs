@@ -0,0 +1,85 @@
+//! Random item drops from mobs and treasure stashes
+
+use crate::{
+    spec::{EntitySpawn, SPECS},
+    Rng,
+};
+use calx::WeightedChoice;
+use rand::Rng as _;
+use serde::{Deserialize, Serialize};
+use std::str::FromStr;
+
+/// Weighted table of items that can be found somewhere.
+#[derive(Clone, Eq, PartialEq, Hash, Debug, Default, Serialize, Deserialize)]
+pub struct LootTable {
+    /// Percent chance of getting anything at all at depth zero.
+    pub chance: u32,
+    /// Item spec names and their relative weights.
+    pub items: Vec<(String, u32)>,
+}
+
+impl LootTable {
+    pub fn new(chance: u32, items: &[(&str, u32)]) -> LootTable {
+        LootTable {
+            chance,
+            items: items.iter().map(|&(n, w)| (n.to_string(), w)).collect(),
+        }
+    }
+
+    /// Chance of finding something grows with depth.
+    pub fn chance_at(&self, depth: i32) -> u32 {
+        if self.chance == 0 {
+            return 0;
+        }
+        (self.chance + 5 * depth.max(0) as u32).min(100)
+    }
+
+    /// Roll the table for loot found at the given depth.
+    ///
+    /// Items that are too deep to spawn at the depth are left out.
+    pub fn roll(&self, rng: &mut Rng, depth: i32) -> Option<EntitySpawn> {
+        if rng.gen_range(0..100) >= self.chance_at(depth) {
+            return None;
+        }
+
+        let (name, _) = self
+            .items
+            .iter()
+            .filter(|(name, _)| {
+                EntitySpawn::from_str(name)
+                    .ok()
+                    .and_then(|s| SPECS.get(&s).map(|spec| spec.min_depth() <= depth))
+                    .unwrap_or(false)
+            })
+            .weighted_choice(rng, |(_, weight)| *weight as f32)?;
+        EntitySpawn::from_str(name).ok()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::LootTable;
+    use crate::spec::EntitySpawn;
+    use calx::seeded_rng;
+    use std::str::FromStr;
+
+    #[test]
+    fn test_loot_table() {
+        let table = LootTable::new(50, &[("sword", 1), ("wand of fireball", 1)]);
+        assert_eq!(table.chance_at(0), 50);
+        assert_eq!(table.chance_at(20), 100);
+        assert_eq!(LootTable::default().chance_at(20), 0);
+
+        // Same seed, same loot.
+        let roll = |seed: u32, depth| table.roll(&mut seeded_rng(&seed), depth);
+        for seed in 0..100 {
+            assert_eq!(roll(seed, 0), roll(seed, 0));
+        }
+
+        // Deep items only show up deep down.
+        let wand = EntitySpawn::from_str("wand of fireball").unwrap();
+        assert!((0..100).all(|seed| roll(seed, 0) != Some(wand.clone())));
+        assert!((0..100).any(|seed| roll(seed, 10) == Some(wand.clone())));
+        assert!((0..100).all(|seed| roll(seed, 10).is_some()));
+    }
+}
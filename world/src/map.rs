@@ -1,7 +1,7 @@
 //! Map generation for individual sectors
 
 use crate::mapsave::{self, MapPatch};
-use crate::spec::EntitySpawn;
+use crate::spec::{EntitySpawn, PLAYER_SPAWN};
 use crate::terrain::Terrain;
 use calx::{die, CellVector, DenseTextMap, Dir6, HexGeom, IntoPrefab, Noise};
 use euclid::vec2;
@@ -183,6 +183,11 @@ impl Map {
                     cell.spawns.push(EntitySpawn::from_str("dreg").unwrap());
                 }
 
+                '$' => {
+                    cell.terrain = Ground;
+                    cell.treasure = true;
+                }
+
                 c => {
                     die!("Unknown map glyph '{}'", c);
                 }
@@ -194,6 +199,36 @@ impl Map {
         Ok(ret)
     }
 
+    /// Build a map from a hand-authored map patch.
+    ///
+    /// The patch is treated as one big vault. Blocking cells on its edge can get doors dug into
+    /// them, everything else is left as authored. A player spawn in the patch marks the player
+    /// entrance instead of spawning anything.
+    pub fn new_patch(patch: &MapPatch) -> Map {
+        let cells: IndexMap<CellVector, (Terrain, Vec<EntitySpawn>)> = patch.iter().collect();
+        let mut ret = Map::default();
+
+        for (&pos, (terrain, spawns)) in &cells {
+            let is_border_pos = !calx::hex_neighbors(pos).all(|p| cells.contains_key(&p));
+            let mut cell = MapCell::new_terrain(*terrain).interior();
+            if is_border_pos && terrain.blocks_walk() {
+                cell = cell.border();
+            }
+
+            for spawn in spawns {
+                if *spawn == *PLAYER_SPAWN {
+                    ret.player_entrance = Some(pos);
+                } else {
+                    cell.spawns.push(spawn.clone());
+                }
+            }
+
+            ret.insert(pos, cell);
+        }
+
+        ret
+    }
+
     /// Build a random rectangular room.
     pub fn new_plain_room(rng: &mut (impl Rng + ?Sized)) -> Map {
         let (w, h) = (rng.gen_range(2..8), rng.gen_range(2..8));
@@ -369,6 +404,33 @@ impl Map {
         }
     }
 
+    /// Paste a hand-authored map on top of this one in the given position.
+    ///
+    /// Unlike with `place_room_at`, the pasted cells always replace the existing ones. Bumper
+    /// cells and cells that fall outside this map are skipped, and pasted cells with no terrain
+    /// of their own keep the existing terrain. The player entrance of the pasted map overrides
+    /// the existing one.
+    pub fn paste(&mut self, offset: CellVector, room: &Map) {
+        for (&p, c) in room {
+            let pos = p + offset;
+            if c.is_bumper() || !self.contains(pos) {
+                continue;
+            }
+
+            let mut c = c.clone();
+            if c.terrain == Terrain::Empty {
+                c.terrain = self[pos].terrain;
+            }
+            self.insert(pos, c);
+        }
+
+        if let Some(pos) = room.player_entrance {
+            if self.contains(pos + offset) {
+                self.player_entrance = Some(pos + offset);
+            }
+        }
+    }
+
     /// Helper function to randomly place a room
     pub fn place_room(
         &mut self,
@@ -654,6 +716,8 @@ fn cut_floodfilled_region(
 pub struct MapCell {
     pub terrain: Terrain,
     pub spawns: Vec<EntitySpawn>,
+    /// Treasure gets rolled here when the map is placed at a known depth.
+    pub treasure: bool,
     can_dig: bool,
     vault_kind: Option<VaultKind>,
 }
@@ -663,6 +727,7 @@ impl Default for MapCell {
         MapCell {
             terrain: Terrain::Empty,
             spawns: Vec::new(),
+            treasure: false,
             can_dig: true,
             vault_kind: None,
         }
@@ -684,6 +749,8 @@ impl MapCell {
 
     pub fn is_interior(&self) -> bool { self.vault_kind == Some(VaultKind::Interior) }
 
+    pub fn can_dig(&self) -> bool { self.can_dig }
+
     /// This is a fake cell that doesn't describe actual terrain but limits the positioning of a
     /// vault to ensure that you can connect to its entrance.
     pub fn is_bumper(&self) -> bool { self.terrain == Terrain::Empty && self.can_dig }
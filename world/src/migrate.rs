@@ -0,0 +1,255 @@
+//! Upgrading saved games from older game versions
+//!
+//! Saves from older versions are deserialized into `SaveData`, an intermediate representation of
+//! the save format that tolerates the differences between the supported versions. The upgrade
+//! steps in `MIGRATIONS` are then applied in order until the data matches the current version, and
+//! the result is converted into the current `World`.
+//!
+//! Every change to the saved game state bumps `GAME_VERSION` and appends an upgrade step, even if
+//! old saves would still deserialize. Add a field or a legacy type to `SaveData` that can read both
+//! the old and the new format and have the upgrade step fill in the new data. Note that calx-ecs
+//! components always deserialize as empty when missing, so a new component needs a step that
+//! fills it in for the existing entities unless having none is correct for them. Keep a save
+//! fixture from the old version as a test.
+
+use crate::{
+    spatial::Spatial, spec::EntitySpawn, world::GAME_VERSION, world_cache::WorldCache, Command,
+    Distribution, Ecs, ExternalEntity, Location, Rng, SaveError, Terrain, World, WorldSeed,
+};
+use calx::{History, IncrementalState};
+use calx_ecs::Entity;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet, HashSet};
+
+/// Upgrade step from one save version to the next.
+struct Migration {
+    from: &'static str,
+    to: &'static str,
+    upgrade: fn(&mut SaveData),
+}
+
+/// Upgrade steps in the order they must be applied.
+const MIGRATIONS: &[Migration] = &[
+    Migration {
+        from: "0.1.0",
+        to: "0.2.0",
+        upgrade: add_kill_count,
+    },
+    Migration {
+        from: "0.2.0",
+        to: "0.3.0",
+        upgrade: add_death_effects,
+    },
+    Migration {
+        from: "0.3.0",
+        to: "0.4.0",
+        upgrade: add_identified,
+    },
+];
+
+/// Just the world version field of a saved game.
+///
+/// Deserialized first so that the save can be routed to the correct loader even if the rest of the
+/// format has changed.
+#[derive(Deserialize)]
+struct SaveHeader {
+    state: WorldHeader,
+}
+
+#[derive(Deserialize)]
+struct WorldHeader {
+    version: String,
+}
+
+/// Saved game in a form that can be read from all supported save versions.
+#[derive(Serialize, Deserialize)]
+struct SaveData {
+    state: WorldData,
+    history: History<WorldSeed, Command>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct WorldData {
+    version: String,
+    ecs: Ecs,
+    world_cache: WorldCache,
+    generated_spawns: HashSet<(Location, EntitySpawn)>,
+    spatial: Spatial,
+    /// Added in 0.3.0.
+    #[serde(default)]
+    terrain_changes: BTreeMap<Location, Terrain>,
+    /// Added in 0.4.0.
+    #[serde(default)]
+    identified: BTreeSet<String>,
+    flags: FlagsData,
+    rng: Rng,
+}
+
+#[derive(Serialize, Deserialize)]
+struct FlagsData {
+    camera: Location,
+    tick: u64,
+    anim_tick: u64,
+    player_acted: bool,
+    player: Option<Entity>,
+    depth: i32,
+    /// Added in 0.2.0, reconstructed by the upgrade step.
+    #[serde(default)]
+    kills: u32,
+}
+
+/// Deserialize a saved game, upgrading it to the current version if needed.
+pub(crate) fn load(text: &str) -> Result<IncrementalState<World>, SaveError> {
+    let header: SaveHeader = ron::de::from_str(text)?;
+    let version = header.state.version;
+    if version == GAME_VERSION {
+        return Ok(ron::de::from_str(text)?);
+    }
+
+    let first = MIGRATIONS
+        .iter()
+        .position(|m| m.from == version)
+        .ok_or(SaveError::VersionMismatch(version))?;
+
+    let mut save: SaveData = ron::de::from_str(text)?;
+    for step in &MIGRATIONS[first..] {
+        debug_assert_eq!(save.state.version, step.from);
+        (step.upgrade)(&mut save);
+        save.state.version = step.to.to_string();
+    }
+    debug_assert_eq!(save.state.version, GAME_VERSION);
+
+    // Round-trip through the serialized form to build the current types.
+    Ok(ron::de::from_str(&ron::ser::to_string(&save)?)?)
+}
+
+/// 0.2.0: Flags got a counter for mobs killed by the player.
+fn add_kill_count(save: &mut SaveData) {
+    // In 0.1.0 every mob came from a worldgen spawn and only the player could kill them, so the
+    // spawned mobs that are no longer around are the player's kills.
+    let state = &save.state;
+    let mut rng = calx::seeded_rng(&1);
+    let spawned = state
+        .generated_spawns
+        .iter()
+        .filter(|(_, spawn)| spawn.sample(&mut rng).loadout.brain.is_some())
+        .count();
+    let alive = state
+        .ecs
+        .brain
+        .ent_iter()
+        .filter(|&&e| Some(e) != state.flags.player)
+        .count();
+    save.state.flags.kills = spawned.saturating_sub(alive) as u32;
+}
+
+/// 0.3.0: Mobs got death effects from their specs and terrain could be changed during play.
+fn add_death_effects(save: &mut SaveData) {
+    // Nothing could change terrain before, so the empty default for terrain changes is correct.
+    let ecs = &mut save.state.ecs;
+    let mobs: Vec<Entity> = ecs.brain.ent_iter().cloned().collect();
+    for e in mobs {
+        if ecs.on_death.get(e).is_some() {
+            continue;
+        }
+        let spec = ecs
+            .desc
+            .get(e)
+            .and_then(|desc| ExternalEntity::from_name(&desc.singular_name).ok());
+        if let Some(on_death) = spec.and_then(|spec| spec.loadout.on_death) {
+            ecs.on_death.insert(e, on_death);
+        }
+    }
+}
+
+/// 0.4.0: Wands and scrolls start out unidentified.
+fn add_identified(save: &mut SaveData) {
+    // The player has seen the real names of the items they carry, keep them known. Items weren't
+    // cursed before, so the default uncursed flag of the existing items is correct.
+    let state = &mut save.state;
+    if let Some(player) = state.flags.player {
+        for (_, e) in state.spatial.entities_in(player) {
+            if let Some(desc) = state.ecs.desc.get(e) {
+                state.identified.insert(desc.singular_name.clone());
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{load, MIGRATIONS};
+    use crate::world::GAME_VERSION;
+    use crate::{Command, ExternalEntity, SaveError};
+
+    #[test]
+    fn test_migration_chain() {
+        // Steps must form an unbroken chain that ends at the current version.
+        for pair in MIGRATIONS.windows(2) {
+            assert_eq!(pair[0].to, pair[1].from);
+        }
+        assert_eq!(
+            MIGRATIONS.last().map_or(GAME_VERSION, |m| m.to),
+            GAME_VERSION
+        );
+    }
+
+    #[test]
+    fn test_load_0_1_0() {
+        let mut game = load(include_str!("../fixtures/save-0.1.0.ron")).unwrap();
+        let player = game.player().expect("No player in loaded save");
+        assert!(game.location(player).is_some());
+        assert_eq!(game.kill_count(), 0);
+
+        // Upgraded save can still be played.
+        let tick = game.get_tick();
+        while !game.player_can_act() {
+            game.update(Command::Wait);
+        }
+        game.update(Command::Pass);
+        assert!(game.get_tick() > tick);
+    }
+
+    #[test]
+    fn test_reconstruct_kill_count() {
+        // Saved after the player had killed five mobs.
+        let game = load(include_str!("../fixtures/save-0.1.0-kills.ron")).unwrap();
+        assert_eq!(game.kill_count(), 5);
+    }
+
+    #[test]
+    fn test_load_0_2_0() {
+        let game = load(include_str!("../fixtures/save-0.2.0.ron")).unwrap();
+
+        // Mobs have the death effects of their specs.
+        let mut with_effects = 0;
+        for &e in game.ecs().brain.ent_iter() {
+            let name = game.spawn_name(e).unwrap();
+            let spec = ExternalEntity::from_name(name).unwrap();
+            assert_eq!(game.ecs().on_death.get(e), spec.loadout.on_death.as_ref());
+            if spec.loadout.on_death.is_some() {
+                with_effects += 1;
+            }
+        }
+        assert!(with_effects > 0);
+    }
+
+    #[test]
+    fn test_load_0_3_0() {
+        let game = load(include_str!("../fixtures/save-0.3.0.ron")).unwrap();
+
+        // The player keeps knowing the wands and scrolls they were carrying.
+        let player = game.player().unwrap();
+        let carried = game.entities_in(player);
+        assert!(!carried.is_empty());
+        for (_, e) in carried {
+            assert!(game.is_identified(e));
+        }
+    }
+
+    #[test]
+    fn test_unknown_version() {
+        let text = include_str!("../fixtures/save-0.1.0.ron").replacen("0.1.0", "9.9.9", 1);
+        assert!(matches!(load(&text), Err(SaveError::VersionMismatch(_))));
+    }
+}
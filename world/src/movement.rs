@@ -1,5 +1,6 @@
 //! Logic for movement and game world space
 use crate::{
+    noise::FOOTSTEP_LOUDNESS,
     stats::{Intrinsic, Status},
     ActionOutcome, Location, Sector, World,
 };
@@ -44,16 +45,26 @@ impl World {
     pub(crate) fn really_step(&mut self, e: Entity, dir: Dir6) -> ActionOutcome {
         let origin = self.location(e)?;
         let loc = origin.jump(self, dir);
-        if self.can_enter(e, loc) {
-            self.place_entity(e, loc);
 
+        // Swap places with own companions instead of being blocked by them.
+        let companion = self.mob_at(loc).filter(|&m| {
+            self.leader(m) == Some(e)
+                && self.can_enter_terrain(e, loc)
+                && self.can_enter_terrain(m, origin)
+        });
+
+        if companion.is_some() || self.can_enter(e, loc) {
             let delay = self.action_delay(e);
             debug_assert!(delay > 0);
-            let anim_tick = self.get_anim_tick();
-            if let Some(anim) = self.ecs_mut().anim.get_mut(e) {
-                anim.tween_from = origin;
-                anim.tween_start = anim_tick;
-                anim.tween_duration = delay;
+
+            if let Some(companion) = companion {
+                self.place_entity(companion, origin);
+                self.set_step_tween(companion, loc, delay);
+            }
+            self.place_entity(e, loc);
+            self.set_step_tween(e, origin, delay);
+            if self.is_player(e) {
+                self.make_noise(loc, FOOTSTEP_LOUDNESS, Some(e));
             }
             self.end_turn(e);
             return Some(true);
@@ -62,6 +73,16 @@ impl World {
         None
     }
 
+    /// Animate entity moving to its current location from origin.
+    pub(crate) fn set_step_tween(&mut self, e: Entity, origin: Location, duration: u32) {
+        let anim_tick = self.get_anim_tick();
+        if let Some(anim) = self.ecs_mut().anim.get_mut(e) {
+            anim.tween_from = origin;
+            anim.tween_start = anim_tick;
+            anim.tween_duration = duration;
+        }
+    }
+
     /// Randomly make a confused mob move erratically.
     ///
     /// Return true if confusion kicked in.
@@ -2,7 +2,7 @@
 
 use crate::{
     ai::Brain,
-    effect::{Damage, Effect},
+    effect::{Effect, Targeting},
     msg,
     sector::SECTOR_WIDTH,
     stats::Status,
@@ -43,6 +43,10 @@ impl World {
     pub(crate) fn equip_item(&mut self, e: Entity, parent: Entity, slot: Slot) {
         self.spatial.equip(e, parent, slot);
         self.rebuild_stats(parent);
+
+        if slot.is_equipment_slot() && self.is_cursed(e) && self.is_player(parent) {
+            msg!("[One] [is] cursed!"; self.subject(e));
+        }
     }
 
     pub(crate) fn set_player(&mut self, player: Option<Entity>) { self.flags.player = player; }
@@ -60,7 +64,9 @@ impl World {
             const OVERLAND_FOV_RANGE: i32 = SECTOR_WIDTH;
 
             // Long-range sight while in overworld.
-            let range = if self.is_underground(origin) {
+            let range = if self.has_status(e, Status::Blind) {
+                1
+            } else if self.is_underground(origin) {
                 DEFAULT_FOV_RANGE
             } else {
                 OVERLAND_FOV_RANGE
@@ -128,6 +134,14 @@ impl World {
         }
     }
 
+    pub(crate) fn entity_shoot(&mut self, e: Entity, dir: Dir6) -> ActionOutcome {
+        if self.confused_move(e) {
+            Some(true)
+        } else {
+            self.really_shoot(e, dir)
+        }
+    }
+
     /// The entity spends its action waiting.
     pub(crate) fn idle(&mut self, e: Entity) -> ActionOutcome {
         if self.consume_nutrition(e) {
@@ -179,12 +193,53 @@ impl World {
         use crate::effect::Effect::*;
         match *effect {
             Hit { amount, damage } => {
-                self.damage(target, amount as i32, damage, source);
+                let resistance = self.resistance(target, damage);
+                let amount = amount as i32 * (100 - resistance) / 100;
+
+                if self
+                    .location(target)
+                    .is_some_and(|loc| self.player_sees(loc))
+                {
+                    if resistance >= 100 {
+                        msg!("[One] [is] unharmed by the {}.", damage.noun(); self.subject(target));
+                    } else if resistance > 0 {
+                        msg!("[One] resist[s] the {}.", damage.noun(); self.subject(target));
+                    } else if resistance < 0 {
+                        msg!("The {} sears [one] badly.", damage.noun(); self.subject(target));
+                    }
+                }
+
+                self.damage(target, amount, damage, source);
             }
             Confuse => {
                 self.gain_status(target, Status::Confused, 40);
                 msg!("[One] [is] confused."; self.subject(target));
             }
+            Poison => {
+                self.gain_status(target, Status::Poisoned, 120);
+                msg!("[One] [is] poisoned."; self.subject(target));
+            }
+            Regenerate => {
+                self.gain_status(target, Status::Regenerating, 240);
+                msg!("[One] start[s] to regenerate."; self.subject(target));
+            }
+            Sleep => {
+                self.gain_status(target, Status::Sleeping, 120);
+                msg!("[One] fall[s] asleep."; self.subject(target));
+            }
+            Blind => {
+                self.gain_status(target, Status::Blind, 120);
+                msg!("[One] [is] blinded."; self.subject(target));
+            }
+            Frighten => {
+                self.gain_status(target, Status::Afraid, 120);
+                msg!("[One] panic[s]."; self.subject(target));
+            }
+            Summon(_) => {
+                if let Some(loc) = self.location(target) {
+                    self.apply_effect_to(effect, loc, source);
+                }
+            }
         }
     }
 
@@ -194,6 +249,23 @@ impl World {
         loc: Location,
         source: Option<Entity>,
     ) {
+        if let Effect::Summon(name) = *effect {
+            let spot = Dir6::iter()
+                .map(|&dir| loc.jump(self, dir))
+                .find(|&loc| !self.blocks_walk(loc) && !self.terrain(loc).is_door());
+
+            if let Some(spot) = spot {
+                let mob = self.spawn(&ExternalEntity::from_name(name).unwrap(), spot);
+                if let Some(source) = source {
+                    self.make_companion(mob, source);
+                }
+                msg!("[One] appear[s] in a puff of smoke."; self.subject(mob));
+            } else {
+                msg!("The spell fizzles.");
+            }
+            return;
+        }
+
         if let Some(mob) = self.mob_at(loc) {
             self.apply_effect_to_entity(effect, mob, source);
         }
@@ -214,11 +286,17 @@ impl World {
     ///
     /// This runs regardless of the action speed or awakeness status of the entity. The exact same
     /// is run for player and AI entities.
-    pub(crate) fn heartbeat(&mut self, e: Entity) { self.tick_statuses(e); }
+    pub(crate) fn heartbeat(&mut self, e: Entity) {
+        self.tick_statuses(e);
+        self.tick_mana_regeneration(e);
+    }
 
-    pub(crate) fn use_ability(&mut self, _e: Entity, _a: Ability) -> ActionOutcome {
-        // TODO
-        None
+    /// Cast a spell the entity knows using its own mana.
+    pub(crate) fn use_ability(&mut self, e: Entity, a: Ability) -> ActionOutcome {
+        debug_assert!(!a.is_targeted());
+        self.location(e)?;
+        self.pay_for_spell(e, a)?;
+        self.cast(e, a, None)
     }
 
     pub(crate) fn use_item_ability(
@@ -228,54 +306,122 @@ impl World {
         a: Ability,
     ) -> ActionOutcome {
         debug_assert!(!a.is_targeted());
-        // TODO: Lift to generic ability use method
         if !self.has_ability(item, a) {
             return None;
         }
-        let origin = self.location(e)?;
+        self.location(e)?;
+        self.cast(e, a, None)?;
+        if self.is_player(e) {
+            self.identify(item);
+        }
+        self.drain_charge(item);
+        Some(true)
+    }
 
-        match a {
-            Ability::LightningBolt => {
-                const LIGHTNING_RANGE: u32 = 4;
-                const LIGHTNING_EFFECT: Effect = Effect::Hit {
-                    amount: 12,
-                    damage: Damage::Electricity,
-                };
+    /// Check that the entity knows a spell and spend the mana for it.
+    fn pay_for_spell(&mut self, e: Entity, a: Ability) -> Option<()> {
+        if !self.knows_spell(e, a) {
+            return None;
+        }
+        if !self.spend_mana(e, a.mana_cost()) {
+            if self.is_player(e) {
+                msg!("Not enough mana.");
+            }
+            return None;
+        }
+        Some(())
+    }
 
-                // TODO: Make an API, more efficient lookup of entities within an area
+    /// Make an entity perform an ability as described by the ability's spec.
+    fn cast(&mut self, e: Entity, a: Ability, dir: Option<Dir6>) -> ActionOutcome {
+        const PROJECTILE_TIME: u64 = 8;
 
+        let spec = a.spec();
+        let origin = self.location(e)?;
+        debug_assert_eq!(spec.targeting.is_directed(), dir.is_some());
+
+        let (target, volume) = match (spec.targeting, dir) {
+            (Targeting::Caster, _) => (origin, Volume::point(origin)),
+            (Targeting::Random, _) => {
+                // TODO: Make an API, more efficient lookup of entities within an area
                 let targets: Vec<Entity> = self
-                    .sphere_volume(origin, LIGHTNING_RANGE)
+                    .sphere_volume(origin, spec.range)
                     .0
                     .into_iter()
                     .flat_map(|loc| self.entities_at(loc))
                     .filter(|&x| self.is_mob(x) && x != e)
                     .collect();
 
-                if let Some(target) = targets.choose(self.rng()) {
-                    msg!("There is a peal of thunder.");
-                    let loc = self.location(*target).unwrap();
-                    self.apply_effect(&LIGHTNING_EFFECT, &Volume::point(loc), Some(e));
-                } else {
-                    msg!("The spell fizzles.");
+                match targets.choose(self.rng()) {
+                    Some(&target) => {
+                        let loc = self.location(target)?;
+                        (loc, Volume::point(loc))
+                    }
+                    None => (origin, Volume(Vec::new())),
                 }
             }
-            _ => {
-                msg!("TODO cast untargeted spell {:?}", a);
+            (Targeting::Bolt, Some(dir)) => {
+                let center = self.projected_explosion_center(origin, dir, spec.range);
+                (center, Volume::point(center))
+            }
+            (Targeting::Ball { radius }, Some(dir)) => {
+                let center = self.projected_explosion_center(origin, dir, spec.range);
+                (center, self.sphere_volume(center, radius))
+            }
+            (Targeting::Cone, Some(dir)) => (origin, Volume::cone(self, origin, dir, spec.range)),
+            (Targeting::Beam, Some(dir)) => {
+                let volume = Volume::beam(self, origin, dir, spec.range);
+                (*volume.0.last().unwrap_or(&origin), volume)
             }
+            (_, None) => return None,
+        };
+
+        if volume.0.is_empty() {
+            msg!("The spell fizzles.");
+            return Some(true);
         }
-        self.drain_charge(item);
+
+        if let Some(message) = spec.message {
+            msg!("{}", message);
+        }
+        for effect in &spec.effects {
+            self.apply_effect(effect, &volume, Some(e));
+        }
+        if spec.noise > 0 {
+            self.make_noise(target, spec.noise, Some(e));
+        }
+
+        let mut impact_delay = 0;
+        if let Some(state) = spec.projectile {
+            impact_delay = PROJECTILE_TIME;
+            let anim_tick = self.get_anim_tick();
+            let projectile = self.spawn_fx(target, state);
+            let anim = self.anim_mut(projectile).unwrap();
+            anim.tween_from = origin;
+            anim.tween_start = anim_tick;
+            anim.tween_duration = PROJECTILE_TIME as u32;
+        }
+        if let Some(state) = spec.impact {
+            for &pt in &volume.0 {
+                let fx = self.spawn_fx(pt, state);
+                self.anim_mut(fx).unwrap().anim_start += impact_delay;
+            }
+        }
+
         Some(true)
     }
 
+    /// Cast a directed spell the entity knows using its own mana.
     pub(crate) fn use_targeted_ability(
         &mut self,
-        _e: Entity,
-        _a: Ability,
-        _dir: Dir6,
+        e: Entity,
+        a: Ability,
+        dir: Dir6,
     ) -> ActionOutcome {
-        // TODO
-        None
+        debug_assert!(a.is_targeted());
+        self.location(e)?;
+        self.pay_for_spell(e, a)?;
+        self.cast(e, a, Some(dir))
     }
 
     pub(crate) fn use_targeted_item_ability(
@@ -289,47 +435,10 @@ impl World {
         if !self.has_ability(item, a) {
             return None;
         }
-        let origin = self.location(e)?;
-
-        // TODO: Lift to generic ability use method
-
-        match a {
-            Ability::Fireball => {
-                const FIREBALL_RANGE: u32 = 9;
-                const FIREBALL_RADIUS: u32 = 1;
-                const FIREBALL_EFFECT: Effect = Effect::Hit {
-                    amount: 6,
-                    damage: Damage::Fire,
-                };
-                let center = self.projected_explosion_center(origin, dir, FIREBALL_RANGE);
-                let volume = self.sphere_volume(center, FIREBALL_RADIUS);
-                self.apply_effect(&FIREBALL_EFFECT, &volume, Some(e));
-
-                // TODO: Maybe move anim generation to own procedure?
-                const PROJECTILE_TIME: u64 = 8;
-                for &pt in &volume.0 {
-                    let fx = self.spawn_fx(pt, AnimState::Explosion);
-                    self.anim_mut(fx).unwrap().anim_start += PROJECTILE_TIME;
-                }
-
-                let anim_tick = self.get_anim_tick();
-                let projectile = self.spawn_fx(center, AnimState::Firespell);
-                {
-                    let anim = self.anim_mut(projectile).unwrap();
-                    anim.tween_from = origin;
-                    anim.tween_start = anim_tick;
-                    anim.tween_duration = PROJECTILE_TIME as u32;
-                }
-            }
-            Ability::Confuse => {
-                const CONFUSION_RANGE: u32 = 9;
-
-                let center = self.projected_explosion_center(origin, dir, CONFUSION_RANGE);
-                self.apply_effect(&Effect::Confuse, &Volume::point(center), Some(e));
-            }
-            _ => {
-                msg!("TODO cast directed spell {:?}", a);
-            }
+        self.location(e)?;
+        self.cast(e, a, Some(dir))?;
+        if self.is_player(e) {
+            self.identify(item);
         }
         self.drain_charge(item);
         Some(true)
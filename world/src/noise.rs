@@ -0,0 +1,142 @@
+//! Sounds that travel through the map and wake up sleeping mobs
+
+use crate::{
+    ai::{BrainState, Relation},
+    Location, World,
+};
+use calx::Dir6;
+use calx_ecs::Entity;
+use std::collections::{HashSet, VecDeque};
+
+/// How far the player's footsteps can be heard.
+pub const FOOTSTEP_LOUDNESS: u32 = 4;
+/// How far the sounds of a melee fight can be heard.
+pub const MELEE_LOUDNESS: u32 = 6;
+/// How far an explosion or a thunderclap can be heard.
+pub const EXPLOSION_LOUDNESS: u32 = 12;
+
+impl World {
+    /// Make a noise that wakes up sleeping mobs within earshot.
+    ///
+    /// The noise spreads from origin along walkable terrain and through portals, loudness is the
+    /// number of steps it can travel. Mobs woken up by the noise go after the source of the noise
+    /// if they are hostile to it.
+    pub(crate) fn make_noise(&mut self, origin: Location, loudness: u32, source: Option<Entity>) {
+        let sleepers: Vec<Entity> = self
+            .noise_area(origin, loudness)
+            .into_iter()
+            .flat_map(|loc| self.entities_at(loc))
+            .filter(|&e| Some(e) != source && self.is_npc(e) && !self.is_active(e))
+            .collect();
+
+        for e in sleepers {
+            // Waking up an earlier sleeper may have already woken this one up.
+            if !self.is_active(e) {
+                self.hear_noise(e, source);
+            }
+        }
+    }
+
+    /// Return the locations a noise reaches in the order it reaches them.
+    pub(crate) fn noise_area(&self, origin: Location, loudness: u32) -> Vec<Location> {
+        let mut ret = vec![origin];
+        let mut seen = HashSet::new();
+        let mut edge = VecDeque::new();
+        seen.insert(origin);
+        edge.push_back((origin, 0));
+
+        while let Some((loc, dist)) = edge.pop_front() {
+            if dist >= loudness {
+                continue;
+            }
+            for &dir in Dir6::iter() {
+                let next = loc.jump(self, dir);
+                if seen.contains(&next) || self.terrain_blocks_walk(next) {
+                    continue;
+                }
+                seen.insert(next);
+                ret.push(next);
+                edge.push_back((next, dist + 1));
+            }
+        }
+        ret
+    }
+
+    /// Wake up a sleeping mob in response to a noise.
+    fn hear_noise(&mut self, e: Entity, source: Option<Entity>) {
+        // Go after the noisemaker if it's an enemy, or join in if it's an ally shouting at an
+        // enemy.
+        let target = source.and_then(|s| {
+            if self.is_hostile_to(e, s) {
+                Some(s)
+            } else if let (Some(Relation::Allied), Some(BrainState::Hunting(t))) =
+                (self.relation(e, s), self.brain_state(s))
+            {
+                Some(t).filter(|&t| self.is_hostile_to(e, t))
+            } else {
+                None
+            }
+        });
+
+        if let Some(target) = target {
+            self.designate_enemy(e, target);
+        } else if let Some(brain) = self.ecs_mut().brain.get_mut(e) {
+            brain.state = BrainState::Roaming;
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{ai::BrainState, ExternalEntity, Location, World, WorldSeed, WorldSkeleton};
+    use calx::Incremental;
+
+    #[test]
+    fn test_noise_wakes_mobs() {
+        let mut world = World::from_seed(&WorldSeed {
+            rng_seed: 1,
+            world_skeleton: WorldSkeleton::dungeon_dive(),
+            player_character: ExternalEntity::from_name("player").unwrap(),
+        });
+        let player = world.player().unwrap();
+        let origin = world.location(player).unwrap();
+
+        // Find open spots away from everything.
+        let find_spot = |world: &World, avoid: Location| {
+            world
+                .noise_area(origin, 30)
+                .into_iter()
+                .find(|&loc| {
+                    loc.metric_distance(origin) > 10
+                        && loc.metric_distance(avoid) > 10
+                        && world
+                            .noise_area(loc, 3)
+                            .iter()
+                            .all(|&x| world.mob_at(x).is_none())
+                })
+                .expect("No room for test")
+        };
+        let dreg = ExternalEntity::from_name("dreg").unwrap();
+
+        let loc = find_spot(&world, origin);
+        let sleeper = world.spawn(&dreg, loc);
+        assert!(!world.is_active(sleeper));
+
+        // Quiet noise far away.
+        world.make_noise(origin, 1, Some(player));
+        assert!(!world.is_active(sleeper));
+
+        // Loud enough to reach.
+        world.make_noise(origin, 30, Some(player));
+        assert_eq!(
+            world.brain_state(sleeper),
+            Some(BrainState::Hunting(player))
+        );
+
+        // A noise without a source just wakes the mob up.
+        let loc2 = find_spot(&world, loc);
+        let sleeper2 = world.spawn(&dreg, loc2);
+        world.make_noise(loc2, 2, None);
+        assert_eq!(world.brain_state(sleeper2), Some(BrainState::Roaming));
+    }
+}
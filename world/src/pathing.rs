@@ -0,0 +1,131 @@
+//! Cached pathfinding maps for AI movement
+
+use crate::{stats::Intrinsic, Location, World};
+use calx::{Dijkstra, Dir6};
+use calx_ecs::Entity;
+use std::cell::RefCell;
+use std::collections::HashMap;
+
+/// How far from the target flow fields extend.
+///
+/// Mobs further away than this fall back to moving directly towards the target.
+const FLOW_FIELD_RANGE: u32 = 24;
+
+/// How many flow fields to keep around before the cache gets cleared.
+const MAX_CACHED_FIELDS: usize = 64;
+
+/// Distance-to-target maps for the locations AI mobs are moving towards.
+///
+/// The maps are derived from static terrain data, so they're not saved and the same map is built
+/// for the same target every time. Mobs without hands can't go through doors, so they get separate
+/// maps that route around them.
+#[derive(Default)]
+pub(crate) struct FlowFieldCache {
+    fields: RefCell<HashMap<(Location, bool), HashMap<Location, u32>>>,
+}
+
+impl FlowFieldCache {
+    pub fn clear(&self) { self.fields.borrow_mut().clear(); }
+}
+
+impl World {
+    /// Return the walking distance for the entity from a location to the target along the target's
+    /// flow field.
+    ///
+    /// Returns `None` if the location is too far from the target or not reachable from it.
+    pub(crate) fn flow_distance(&self, e: Entity, target: Location, loc: Location) -> Option<u32> {
+        let key = (target, self.has_intrinsic(e, Intrinsic::Hands));
+        let mut fields = self.flow_fields.fields.borrow_mut();
+        if !fields.contains_key(&key) {
+            // Fields stay valid until the terrain changes, but the player leaves a trail of old
+            // targets behind, so don't let them pile up.
+            if fields.len() >= MAX_CACHED_FIELDS {
+                fields.clear();
+            }
+            fields.insert(key, self.build_flow_field(key.0, key.1));
+        }
+        fields[&key].get(&loc).cloned()
+    }
+
+    /// Build the distance map to target with the passability rules of a mob with or without
+    /// hands.
+    fn build_flow_field(&self, target: Location, hands: bool) -> HashMap<Location, u32> {
+        let neighbors = |&loc: &Location| -> Vec<Location> {
+            Dir6::iter()
+                .map(|&dir| loc.jump(self, dir))
+                .filter(|&next| {
+                    !self.terrain_blocks_walk(next) && (hands || !self.terrain(next).is_door())
+                })
+                .collect()
+        };
+        Dijkstra::from_fn(vec![target], neighbors, FLOW_FIELD_RANGE).weights
+    }
+
+    /// Return the direction an entity should step in to get closer to the target, following the
+    /// flow field.
+    ///
+    /// Prefers the steepest downhill step that the entity can currently take, eg. that isn't
+    /// blocked by another mob or a door the entity can't open.
+    pub(crate) fn flow_dir(&self, e: Entity, target: Location) -> Option<Dir6> {
+        let origin = self.location(e)?;
+        let current = self.flow_distance(e, target, origin)?;
+
+        Dir6::iter()
+            .cloned()
+            .filter_map(|dir| {
+                let loc = origin.jump(self, dir);
+                let dist = self.flow_distance(e, target, loc)?;
+                if dist < current && self.can_enter(e, loc) {
+                    Some((dist, dir))
+                } else {
+                    None
+                }
+            })
+            .min_by_key(|&(dist, _)| dist)
+            .map(|(_, dir)| dir)
+    }
+
+    /// Throw away cached flow fields.
+    ///
+    /// Called when terrain changes or new terrain gets generated.
+    pub(crate) fn invalidate_flow_fields(&self) { self.flow_fields.clear(); }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{ExternalEntity, World, WorldSeed, WorldSkeleton};
+    use calx::Incremental;
+
+    #[test]
+    fn test_flow_field() {
+        let world = World::from_seed(&WorldSeed {
+            rng_seed: 1,
+            world_skeleton: WorldSkeleton::dungeon_dive(),
+            player_character: ExternalEntity::from_name("player").unwrap(),
+        });
+        let player = world.player().unwrap();
+        let origin = world.location(player).unwrap();
+
+        assert_eq!(world.flow_distance(player, origin, origin), Some(0));
+
+        // Following the field from any reachable location leads to the target.
+        let field = world.build_flow_field(origin, true);
+        assert!(field.len() > 1);
+        for (&loc, &dist) in &field {
+            if dist == 0 {
+                continue;
+            }
+            assert!(
+                calx::Dir6::iter()
+                    .any(|&dir| field.get(&loc.jump(&world, dir)) == Some(&(dist - 1))),
+                "Flow field has a local minimum at {:?}",
+                loc
+            );
+        }
+
+        // Mobs without hands are routed around doors.
+        assert!(field.keys().any(|&loc| world.terrain(loc).is_door()));
+        let field = world.build_flow_field(origin, false);
+        assert!(field.keys().all(|&loc| !world.terrain(loc).is_door()));
+    }
+}
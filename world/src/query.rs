@@ -1,7 +1,8 @@
 //! Gameplay logic that answers questions but doesn't change anything
 
 use crate::{
-    fov::SightFov, location::Location, mapsave, spec::EntitySpawn, Ecs, FovStatus, Terrain, World,
+    fov::SightFov, location::Location, mapsave, spec::EntitySpawn, Ecs, FovStatus, Sector, Terrain,
+    World,
 };
 use calx::{Dir6, HexFov, HexFovIter, Noise};
 use calx_ecs::Entity;
@@ -25,9 +26,19 @@ impl World {
     /// Return current time of the world logic clock.
     pub fn get_tick(&self) -> u64 { self.flags.tick }
 
+    /// Return the number of mobs the player has killed.
+    pub fn kill_count(&self) -> u32 { self.flags.kills }
+
     /// Return world RNG seed
     pub fn rng_seed(&self) -> u32 { self.world_cache.seed() }
 
+    /// Return the dungeon depth of the sector the location is in.
+    pub fn depth(&self, loc: Location) -> i32 {
+        self.world_cache
+            .sector_spec(Sector::from(loc))
+            .map_or(0, |spec| spec.depth)
+    }
+
     /// Return reference to the world entity component system.
     pub fn ecs(&self) -> &Ecs { &self.ecs }
 
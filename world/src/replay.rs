@@ -0,0 +1,254 @@
+//! Recorded game sessions that can be played back and verified
+
+use crate::{world::GAME_VERSION, Command, World, WorldSeed};
+use calx::{History, Incremental};
+use serde::{Deserialize, Serialize};
+use std::error::Error;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+/// How many ticks apart the state checksums in a replay are.
+pub const CHECKPOINT_INTERVAL: u64 = 64;
+
+/// Game state checksum at a specific world tick.
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Serialize, Deserialize)]
+pub struct Checkpoint {
+    pub tick: u64,
+    pub hash: u64,
+}
+
+/// Standalone recording of a game session.
+///
+/// A replay is the world seed and the full sequence of commands given to the world, which is
+/// enough to deterministically reconstruct the game. Periodic state checksums are stored along
+/// with the commands so that the playback can detect when the simulation no longer matches the
+/// recording.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Replay {
+    /// Game version that recorded the replay.
+    ///
+    /// Replays from other versions are rejected, changes in game logic will make them desync.
+    pub version: String,
+    pub seed: WorldSeed,
+    pub commands: Vec<Command>,
+    pub checkpoints: Vec<Checkpoint>,
+}
+
+/// Just the version field of a replay file.
+///
+/// Deserialized first so that the version can be checked even if the rest of the format has
+/// changed.
+#[derive(Deserialize)]
+struct ReplayHeader {
+    version: String,
+}
+
+#[derive(Debug)]
+pub enum ReplayError {
+    Io(io::Error),
+    /// Replay file could not be parsed.
+    Format(String),
+    /// Replay was recorded with a different game version.
+    VersionMismatch(String),
+    /// Simulation stopped matching the recording.
+    ///
+    /// The first state difference happened after `last_good_tick` and at or before `tick`.
+    Desync {
+        last_good_tick: u64,
+        tick: u64,
+    },
+}
+
+impl fmt::Display for ReplayError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        use ReplayError::*;
+        match self {
+            Io(e) => write!(f, "{}", e),
+            Format(e) => write!(f, "Bad replay file: {}", e),
+            VersionMismatch(v) => write!(
+                f,
+                "Replay is from version {}, current version is {}",
+                v, GAME_VERSION
+            ),
+            Desync {
+                last_good_tick,
+                tick,
+            } => write!(
+                f,
+                "Replay desyncs between ticks {} and {}",
+                last_good_tick, tick
+            ),
+        }
+    }
+}
+
+impl Error for ReplayError {}
+
+impl From<io::Error> for ReplayError {
+    fn from(e: io::Error) -> Self { ReplayError::Io(e) }
+}
+
+impl From<ron::Error> for ReplayError {
+    fn from(e: ron::Error) -> Self { ReplayError::Format(e.to_string()) }
+}
+
+impl From<ron::de::SpannedError> for ReplayError {
+    fn from(e: ron::de::SpannedError) -> Self { ReplayError::Format(e.to_string()) }
+}
+
+impl Replay {
+    /// Build a replay from game history.
+    ///
+    /// Replays the whole history to generate the state checksums.
+    pub fn new(history: &History<WorldSeed, Command>) -> Replay {
+        let mut checkpoints = Vec::new();
+        let mut world = World::from_seed(&history.seed);
+        for cmd in &history.events {
+            world.update(cmd);
+            if world.get_tick().is_multiple_of(CHECKPOINT_INTERVAL) {
+                checkpoints.push(Checkpoint {
+                    tick: world.get_tick(),
+                    hash: world.state_hash(),
+                });
+            }
+        }
+
+        Replay {
+            version: GAME_VERSION.to_string(),
+            seed: history.seed.clone(),
+            commands: history.events.clone(),
+            checkpoints,
+        }
+    }
+
+    /// Return the game history recorded in the replay.
+    pub fn history(&self) -> History<WorldSeed, Command> {
+        History {
+            seed: self.seed.clone(),
+            events: self.commands.clone(),
+        }
+    }
+
+    /// Play back the replay and check that the simulation matches the recording.
+    pub fn verify(&self) -> Result<World, ReplayError> {
+        self.check_version()?;
+
+        let mut world = World::from_seed(&self.seed);
+        let mut last_good_tick = world.get_tick();
+
+        for cmd in &self.commands {
+            world.update(cmd);
+            match self.matches(&world) {
+                Some(true) => last_good_tick = world.get_tick(),
+                Some(false) => {
+                    return Err(ReplayError::Desync {
+                        last_good_tick,
+                        tick: world.get_tick(),
+                    });
+                }
+                None => {}
+            }
+        }
+
+        Ok(world)
+    }
+
+    /// Compare world state against the recorded checkpoint for the world's current tick.
+    ///
+    /// Returns `None` if there is no checkpoint for the current tick.
+    pub fn matches(&self, world: &World) -> Option<bool> {
+        let tick = world.get_tick();
+        let idx = self
+            .checkpoints
+            .binary_search_by_key(&tick, |c| c.tick)
+            .ok()?;
+        Some(self.checkpoints[idx].hash == world.state_hash())
+    }
+
+    pub fn check_version(&self) -> Result<(), ReplayError> {
+        if self.version != GAME_VERSION {
+            Err(ReplayError::VersionMismatch(self.version.clone()))
+        } else {
+            Ok(())
+        }
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> Result<Replay, ReplayError> {
+        let text = fs::read_to_string(path)?;
+
+        let header: ReplayHeader = ron::de::from_str(&text)?;
+        if header.version != GAME_VERSION {
+            return Err(ReplayError::VersionMismatch(header.version));
+        }
+
+        Ok(ron::de::from_str(&text)?)
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), ReplayError> {
+        let path = path.as_ref();
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        fs::write(path, ron::ser::to_string(self)?)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{Replay, ReplayError};
+    use crate::{Command, ExternalEntity, Runner, WorldSeed, WorldSkeleton};
+    use calx::Dir6;
+
+    #[test]
+    fn test_replay_verify() {
+        let mut runner = Runner::new(WorldSeed {
+            rng_seed: 123,
+            world_skeleton: WorldSkeleton::dungeon_dive(),
+            player_character: ExternalEntity::from_name("player").unwrap(),
+        });
+        for &dir in Dir6::iter().cycle().take(100) {
+            runner.command(Command::Step(dir));
+        }
+
+        let mut replay = Replay::new(runner.history());
+        assert!(!replay.checkpoints.is_empty());
+        let world = replay.verify().unwrap();
+        assert_eq!(world.get_tick(), runner.world().get_tick());
+
+        let saved = ron::ser::to_string(&replay).unwrap();
+        let loaded: Replay = ron::de::from_str(&saved).unwrap();
+        assert!(loaded.verify().is_ok());
+
+        replay.checkpoints[0].hash ^= 1;
+        match replay.verify() {
+            Err(ReplayError::Desync { tick, .. }) => assert_eq!(tick, replay.checkpoints[0].tick),
+            _ => panic!("Tampered replay did not desync"),
+        }
+
+        replay.version = "0.0.0".to_string();
+        assert!(matches!(
+            replay.verify(),
+            Err(ReplayError::VersionMismatch(_))
+        ));
+    }
+
+    #[test]
+    fn test_replay_load_version_mismatch() {
+        let dir = std::env::temp_dir().join(format!("magog-replay-test-{}", std::process::id()));
+        let path = dir.join("old.ron");
+
+        // The header is checked before the rest, so format changes still give a version error.
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(&path, r#"(version: "0.0.0", moves: [1, 2, 3])"#).unwrap();
+        match Replay::load(&path) {
+            Err(ReplayError::VersionMismatch(v)) => assert_eq!(v, "0.0.0"),
+            Err(e) => panic!("Unexpected error {}", e),
+            Ok(_) => panic!("Loaded replay from another version"),
+        }
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,199 @@
+//! Headless game driver that runs the world without a display frontend.
+
+use crate::{Bot, Command, Location, World, WorldSeed};
+use calx::{History, IncrementalState};
+use serde::{Deserialize, Serialize};
+
+/// Snapshot of game state after a turn, for logging and analysis.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct TurnReport {
+    /// World logic clock time.
+    pub tick: u64,
+    /// Current player health, `None` if the player is dead.
+    pub hp: Option<i32>,
+    /// Maximum player health, `None` if the player is dead.
+    pub max_hp: Option<i32>,
+    /// Player position, `None` if the player is dead.
+    pub location: Option<Location>,
+    /// Dungeon depth of the player's position, `None` if the player is dead.
+    pub depth: Option<i32>,
+    /// Number of mobs the player has killed.
+    pub kills: u32,
+}
+
+/// How an automated game session ended.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub enum Outcome {
+    /// The player character died.
+    PlayerDied,
+    /// The player kept giving commands that did not advance game time.
+    SoftLock,
+    /// The session ran out of turns.
+    TurnLimit,
+}
+
+/// Headless game session.
+///
+/// Drives the world with the same command protocol as the windowed game so recorded command
+/// sequences play out identically in both.
+pub struct Runner {
+    world: IncrementalState<World>,
+}
+
+impl Runner {
+    pub fn new(seed: WorldSeed) -> Runner {
+        Runner {
+            world: IncrementalState::new(seed),
+        }
+    }
+
+    /// Access the current world state.
+    pub fn world(&self) -> &World { &self.world }
+
+    /// Return the seed and command log that reproduce the current state.
+    pub fn history(&self) -> &History<WorldSeed, Command> { self.world.history() }
+
+    /// Run the world until the player can act or is dead.
+    pub fn wait_for_player(&mut self) {
+        while self.world.player().is_some() && !self.world.player_can_act() {
+            self.world.update(Command::Wait);
+        }
+    }
+
+    /// Give a command to the player.
+    ///
+    /// Fast-forwards the world to the player's next turn before applying the command. Returns
+    /// false if the command is not valid in the current world state.
+    pub fn command(&mut self, cmd: Command) -> bool {
+        if !self.world.can_command(&cmd) {
+            return false;
+        }
+
+        if cmd == Command::Wait {
+            // Only valid outside player's turn, pass it through as is.
+            self.world.update(cmd);
+            return true;
+        }
+
+        self.wait_for_player();
+
+        if self.world.player().is_none() {
+            return false;
+        }
+
+        debug_assert!(self.world.player_can_act());
+        self.world.update(cmd);
+        true
+    }
+
+    /// Let a bot choose and perform the player's next action.
+    ///
+    /// Returns whether game time advanced past the player's turn. Does nothing if the player is
+    /// dead.
+    pub fn bot_turn(&mut self, bot: &mut dyn Bot) -> bool {
+        self.wait_for_player();
+        if self.world.player().is_none() {
+            return false;
+        }
+
+        let cmd = bot.act(&self.world);
+        self.command(cmd);
+        !self.world.player_can_act()
+    }
+
+    /// Run a bot until the player dies, the bot gets stuck or the turn limit is reached.
+    ///
+    /// The callback is called with the game state after every bot turn.
+    pub fn play(
+        &mut self,
+        bot: &mut dyn Bot,
+        max_turns: usize,
+        mut on_turn: impl FnMut(&TurnReport),
+    ) -> Outcome {
+        // How many times in a row the bot can fail to advance time before it's considered stuck.
+        const STALL_LIMIT: usize = 100;
+
+        let mut stalls = 0;
+        for _ in 0..max_turns {
+            if self.bot_turn(bot) {
+                stalls = 0;
+            } else {
+                stalls += 1;
+            }
+            on_turn(&self.report());
+
+            if self.world.game_over() {
+                return Outcome::PlayerDied;
+            }
+            if stalls >= STALL_LIMIT {
+                return Outcome::SoftLock;
+            }
+        }
+        Outcome::TurnLimit
+    }
+
+    /// Return a summary of the current game state.
+    pub fn report(&self) -> TurnReport {
+        let player = self.world.player();
+        TurnReport {
+            tick: self.world.get_tick(),
+            hp: player.map(|p| self.world.hp(p)),
+            max_hp: player.map(|p| self.world.max_hp(p)),
+            location: player.and_then(|p| self.world.location(p)),
+            depth: player
+                .and_then(|p| self.world.location(p))
+                .map(|loc| self.world.depth(loc)),
+            kills: self.world.kill_count(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{Outcome, Runner};
+    use crate::{
+        Aggressor, Command, Explorer, ExternalEntity, RandomWalker, WorldSeed, WorldSkeleton,
+    };
+    use calx::{Dir6, IncrementalState};
+
+    fn dungeon_seed(rng_seed: u32) -> WorldSeed {
+        WorldSeed {
+            rng_seed,
+            world_skeleton: WorldSkeleton::dungeon_dive(),
+            player_character: ExternalEntity::from_name("player").unwrap(),
+        }
+    }
+
+    #[test]
+    fn test_replay_matches_run() {
+        let mut runner = Runner::new(dungeon_seed(123));
+        for &dir in Dir6::iter().cycle().take(30) {
+            runner.command(Command::Step(dir));
+            runner.wait_for_player();
+        }
+        let report = runner.report();
+        assert!(report.tick > 0);
+        assert_eq!(report.depth, Some(0));
+
+        let replay = Runner {
+            world: IncrementalState::from(runner.history().clone()),
+        };
+        assert_eq!(replay.report(), report);
+    }
+
+    #[test]
+    fn test_bots_do_not_get_stuck() {
+        for seed in 1..4 {
+            let bots: Vec<Box<dyn crate::Bot>> = vec![
+                Box::new(RandomWalker::new(seed)),
+                Box::new(Explorer),
+                Box::new(Aggressor::default()),
+            ];
+            for mut bot in bots {
+                let mut runner = Runner::new(dungeon_seed(seed));
+                let outcome = runner.play(&mut *bot, 300, |_| {});
+                assert_ne!(outcome, Outcome::SoftLock, "Bot stuck with seed {}", seed);
+            }
+        }
+    }
+}
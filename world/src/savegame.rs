@@ -0,0 +1,233 @@
+//! Saved games stored as screenshot images with the game state embedded in them
+
+use crate::{migrate, world::GAME_VERSION, World};
+use calx::{stego, IncrementalState};
+use image::RgbImage;
+use std::error::Error;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+/// Width of save slot thumbnail images.
+pub const THUMBNAIL_WIDTH: u32 = 128;
+/// Height of save slot thumbnail images.
+pub const THUMBNAIL_HEIGHT: u32 = 72;
+
+#[derive(Debug)]
+pub enum SaveError {
+    /// There is no save in the named slot.
+    NotFound(String),
+    /// Slot name can't be used as a file name.
+    BadSlotName(String),
+    Io(io::Error),
+    /// Save file is not a readable image.
+    Image(image::ImageError),
+    /// Save image does not contain embedded game data.
+    NoPayload,
+    /// Embedded game data could not be parsed.
+    Format(String),
+    /// Save was made with a game version that can't be upgraded to the current one.
+    VersionMismatch(String),
+}
+
+impl fmt::Display for SaveError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        use SaveError::*;
+        match self {
+            NotFound(slot) => write!(f, "No saved game in slot '{}'", slot),
+            BadSlotName(slot) => write!(f, "Invalid save slot name '{}'", slot),
+            Io(e) => write!(f, "{}", e),
+            Image(e) => write!(f, "Bad save image: {}", e),
+            NoPayload => write!(f, "Save image does not contain game data"),
+            Format(e) => write!(f, "Corrupt save data: {}", e),
+            VersionMismatch(v) => write!(
+                f,
+                "Save is from version {}, current version is {}",
+                v, GAME_VERSION
+            ),
+        }
+    }
+}
+
+impl Error for SaveError {}
+
+impl From<io::Error> for SaveError {
+    fn from(e: io::Error) -> Self { SaveError::Io(e) }
+}
+
+impl From<image::ImageError> for SaveError {
+    fn from(e: image::ImageError) -> Self { SaveError::Image(e) }
+}
+
+impl From<ron::Error> for SaveError {
+    fn from(e: ron::Error) -> Self { SaveError::Format(e.to_string()) }
+}
+
+impl From<ron::de::SpannedError> for SaveError {
+    fn from(e: ron::de::SpannedError) -> Self { SaveError::Format(e.to_string()) }
+}
+
+/// Listing information for an existing save.
+pub struct SaveSlot {
+    pub name: String,
+    pub modified: SystemTime,
+    /// Downscaled screenshot from when the game was saved.
+    pub thumbnail: RgbImage,
+}
+
+/// Reads and writes saved games in a directory of named slots.
+///
+/// Each slot is a PNG screenshot of the game with the game state steganographically embedded in
+/// it.
+pub struct SaveManager {
+    dir: PathBuf,
+}
+
+impl SaveManager {
+    pub fn new(dir: impl Into<PathBuf>) -> SaveManager { SaveManager { dir: dir.into() } }
+
+    /// Save manager using the game's user data directory.
+    pub fn user_saves() -> SaveManager {
+        SaveManager::new(calx::app_data_path("magog").join("saves"))
+    }
+
+    pub fn dir(&self) -> &Path { &self.dir }
+
+    fn slot_path(&self, slot: &str) -> Result<PathBuf, SaveError> {
+        let is_valid = !slot.is_empty()
+            && slot
+                .chars()
+                .all(|c| c.is_alphanumeric() || c == '-' || c == '_');
+        if !is_valid {
+            return Err(SaveError::BadSlotName(slot.to_string()));
+        }
+        Ok(self.dir.join(format!("{}.png", slot)))
+    }
+
+    /// Save the game into a slot, overwriting any previous save there.
+    ///
+    /// The cover image should be a screenshot of the game, it is shown in the save listing.
+    pub fn save(
+        &self,
+        slot: &str,
+        game: &IncrementalState<World>,
+        cover: &RgbImage,
+    ) -> Result<(), SaveError> {
+        let path = self.slot_path(slot)?;
+        fs::create_dir_all(&self.dir)?;
+
+        let enc = ron::ser::to_string(game)?;
+        let save = stego::embed_gzipped(cover, enc.as_bytes());
+        save.save_with_format(path, image::ImageFormat::Png)?;
+        Ok(())
+    }
+
+    /// Load the game from a slot.
+    pub fn load(&self, slot: &str) -> Result<IncrementalState<World>, SaveError> {
+        let path = self.slot_path(slot)?;
+        if !path.exists() {
+            return Err(SaveError::NotFound(slot.to_string()));
+        }
+
+        let save = image::open(path)?.to_rgb8();
+        let data = stego::extract(&save).map_err(|_| SaveError::NoPayload)?;
+        let text = String::from_utf8(data).map_err(|e| SaveError::Format(e.to_string()))?;
+
+        migrate::load(&text)
+    }
+
+    pub fn delete(&self, slot: &str) -> Result<(), SaveError> {
+        let path = self.slot_path(slot)?;
+        if !path.exists() {
+            return Err(SaveError::NotFound(slot.to_string()));
+        }
+        fs::remove_file(path)?;
+        Ok(())
+    }
+
+    /// List the existing saves, most recent first.
+    ///
+    /// Files in the save directory that aren't readable images are skipped.
+    pub fn list(&self) -> Result<Vec<SaveSlot>, SaveError> {
+        if !self.dir.exists() {
+            return Ok(Vec::new());
+        }
+
+        let mut ret = Vec::new();
+        for entry in fs::read_dir(&self.dir)? {
+            let path = entry?.path();
+            if path.extension().and_then(|e| e.to_str()) != Some("png") {
+                continue;
+            }
+            let name = match path.file_stem().and_then(|s| s.to_str()) {
+                Some(name) => name.to_string(),
+                None => continue,
+            };
+            let image = match image::open(&path) {
+                Ok(image) => image,
+                Err(_) => continue,
+            };
+            ret.push(SaveSlot {
+                name,
+                modified: fs::metadata(&path)?.modified()?,
+                thumbnail: image.thumbnail(THUMBNAIL_WIDTH, THUMBNAIL_HEIGHT).to_rgb8(),
+            });
+        }
+
+        ret.sort_by_key(|slot| std::cmp::Reverse(slot.modified));
+        Ok(ret)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{SaveError, SaveManager};
+    use crate::{Command, ExternalEntity, World, WorldSeed, WorldSkeleton};
+    use calx::{Dir6, IncrementalState};
+    use image::RgbImage;
+    use std::fs;
+
+    #[test]
+    fn test_save_slots() {
+        let dir = std::env::temp_dir().join(format!("magog-save-test-{}", std::process::id()));
+        let saves = SaveManager::new(&dir);
+
+        let mut game: IncrementalState<World> = IncrementalState::new(WorldSeed {
+            rng_seed: 1,
+            world_skeleton: WorldSkeleton::dungeon_dive(),
+            player_character: ExternalEntity::from_name("player").unwrap(),
+        });
+        for &dir in Dir6::iter() {
+            while !game.player_can_act() {
+                game.update(Command::Wait);
+            }
+            game.update(Command::Step(dir));
+        }
+        let cover = RgbImage::new(640, 360);
+
+        assert!(matches!(saves.load("slot1"), Err(SaveError::NotFound(_))));
+        assert!(matches!(
+            saves.save("../slot1", &game, &cover),
+            Err(SaveError::BadSlotName(_))
+        ));
+
+        saves.save("slot1", &game, &cover).unwrap();
+        let loaded = saves.load("slot1").unwrap();
+        assert_eq!(loaded.state_hash(), game.state_hash());
+
+        let list = saves.list().unwrap();
+        assert_eq!(list.len(), 1);
+        assert_eq!(list[0].name, "slot1");
+
+        // A plain image without a payload.
+        cover.save(dir.join("slot2.png")).unwrap();
+        assert!(matches!(saves.load("slot2"), Err(SaveError::NoPayload)));
+
+        saves.delete("slot1").unwrap();
+        assert!(matches!(saves.load("slot1"), Err(SaveError::NotFound(_))));
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
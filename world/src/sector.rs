@@ -3,13 +3,16 @@
 use crate::{
     location::Location,
     map::{Map, MapCell},
+    mapsave::MapPatch,
     spec::{self, EntitySpawn, Spec},
     terrain::Terrain,
-    vaults, {Distribution, Rng},
+    vaults,
+    world_cache::PLAYER_START_SECTOR,
+    {Distribution, Rng},
 };
 use calx::{
-    die, project, seeded_rng, CellSpace, CellVector, ProjectVec, RngExt, Space, StaggeredHexSpace,
-    WeightedChoice,
+    die, project, seeded_rng, CellSpace, CellVector, HexGeom, ProjectVec, RngExt, Space,
+    StaggeredHexSpace, WeightedChoice,
 };
 use euclid::{vec2, vec3, Vector2D};
 use lazy_static::lazy_static;
@@ -17,9 +20,10 @@ use log::{debug, warn};
 use rand::seq::SliceRandom;
 use rand::Rng as _;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
 use std::error::Error;
 use std::ops::{Add, Deref, DerefMut};
+use std::slice;
 use std::str::FromStr;
 use std::sync::Arc;
 
@@ -289,6 +293,32 @@ pub enum SectorDir {
     Down,
 }
 
+impl SectorDir {
+    pub fn iter() -> slice::Iter<'static, SectorDir> {
+        use SectorDir::*;
+        const DIRS: [SectorDir; 8] = [
+            Northeast, East, Southeast, Southwest, West, Northwest, Up, Down,
+        ];
+        DIRS.iter()
+    }
+
+    pub fn opposite(self) -> SectorDir {
+        use SectorDir::*;
+        match self {
+            Northeast => Southwest,
+            East => West,
+            Southeast => Northwest,
+            Southwest => Northeast,
+            West => East,
+            Northwest => Southeast,
+            Up => Down,
+            Down => Up,
+        }
+    }
+
+    pub fn is_vertical(self) -> bool { self == SectorDir::Up || self == SectorDir::Down }
+}
+
 impl From<SectorDir> for SectorVec {
     fn from(dir: SectorDir) -> Self {
         use SectorDir::*;
@@ -405,6 +435,9 @@ pub enum Biome {
     Desert,
     Water,
     City,
+    Sewer,
+    Cave,
+    Tomb,
 }
 
 impl Default for Biome {
@@ -412,6 +445,29 @@ impl Default for Biome {
 }
 
 impl Biome {
+    /// Return whether the biome's maps are made with the dungeon generator.
+    pub fn is_underground(self) -> bool {
+        use Biome::*;
+        matches!(self, Dungeon | Sewer | Cave | Tomb)
+    }
+
+    /// Convert generic dungeon terrain into the biome's own terrain palette.
+    fn dungeon_terrain(self, rng: &mut Rng, terrain: Terrain) -> Terrain {
+        use Biome::*;
+        use Terrain::*;
+
+        match (self, terrain) {
+            // Flooded tunnels.
+            (Sewer, Ground) if rng.one_chance_in(3) => Shallows,
+            // Natural caverns, no masonry.
+            (Cave, Wall) | (Cave, Window) => Rock,
+            (Cave, Door) => Ground,
+            // Sand drifted in from the desert above.
+            (Tomb, Ground) => Sand,
+            (_, t) => t,
+        }
+    }
+
     /// Return terrain for the biome at a given position.
     ///
     /// Wilderness biomes produce useful terrain via just this function. Dungeon terrains will just
@@ -422,7 +478,7 @@ impl Biome {
         // Get the tile-less ones out of the way.
         // XXX: Should Dungeon and City have herringbone sets too?
         match self {
-            Dungeon => return Terrain::Rock,
+            Dungeon | Sewer | Cave | Tomb => return Terrain::Rock,
             Water => return Terrain::Water,
             City => return Terrain::Ground,
             Mountain => return Terrain::Rock,
@@ -455,16 +511,81 @@ impl Biome {
     }
 }
 
+/// Hand-authored map for a sector.
+///
+/// The authored map is drawn over the sector's biome terrain. Stairs to neighboring sectors are
+/// added if the map doesn't have its own, so authored maps should leave some room around the
+/// sector's default stairwell positions.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub enum SectorMap {
+    /// ASCII map in the `Map::new_vault` format, centered on the sector.
+    Vault(String),
+    /// Map patch at an absolute location, like the ones in `WorldData` converted from Tiled maps.
+    Patch { offset: Location, patch: MapPatch },
+}
+
+impl SectorMap {
+    /// Build the authored map and its offset from the origin of the sector.
+    pub(crate) fn build(&self, sector: Sector) -> Result<(CellVector, Map), Box<dyn Error>> {
+        match self {
+            SectorMap::Vault(text) => {
+                let map = Map::new_vault(text)?;
+                let (mut min, mut max) = (vec2(i32::MAX, i32::MAX), vec2(i32::MIN, i32::MIN));
+                for (p, _) in &map {
+                    min = min.min(*p);
+                    max = max.max(*p);
+                }
+                let center = Location::from(sector).v2_at(sector.center()).unwrap();
+                Ok((center - (min + max) / 2, map))
+            }
+            SectorMap::Patch { offset, patch } => {
+                let offset = Location::from(sector)
+                    .v2_at(*offset)
+                    .ok_or("Map patch is not on the sector's floor")?;
+                Ok((offset, Map::new_patch(patch)))
+            }
+        }
+    }
+}
+
+/// How a sector connects to a neighboring sector.
+///
+/// There are no locked gates, the game has no keys to open them with. Use `Sealed` for a
+/// connection the player must not pass.
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Default, Serialize, Deserialize)]
+pub enum Connection {
+    /// Connect to the neighbor if it exists.
+    #[default]
+    Open,
+    /// Never connect, from either side.
+    Sealed,
+    /// Allow travel from this sector to the neighbor but not back.
+    ///
+    /// Only stairs can be one-way, other one-way connections are sealed.
+    OneWay,
+}
+
 /// Specification for generating a Sector's map.
 ///
 /// This serves as the top-level entry point to map generation routines.
 #[derive(Clone, Debug, Default, Serialize, Deserialize)]
 pub struct SectorSpec {
-    // TODO: Sectors can be predefined maps.
-    // TODO: flags for blocked connection to N,E,W,S,up and down neighbor sectors
-    // By default create path/stairs if adjacent sector exists.
     pub depth: i32,
     pub biome: Biome,
+    /// Use a hand-authored map instead of generating one.
+    #[serde(default)]
+    pub map: Option<SectorMap>,
+    /// Connections to neighboring sectors that aren't open.
+    ///
+    /// By default paths and stairs are made to every adjacent sector that exists.
+    #[serde(default)]
+    pub connections: BTreeMap<SectorDir, Connection>,
+}
+
+impl SectorSpec {
+    pub fn connection(&self, dir: SectorDir) -> Connection {
+        self.connections.get(&dir).cloned().unwrap_or_default()
+    }
 }
 
 #[derive(Clone, Default, Serialize, Deserialize)]
@@ -481,84 +602,57 @@ impl DerefMut for WorldSkeleton {
 }
 
 impl WorldSkeleton {
-    pub fn dungeon_dive() -> WorldSkeleton {
-        let mut ret = WorldSkeleton::default();
-        for depth in 0..10 {
-            let sector = Sector::new(0, 0, -(depth as i16));
-            let spec = SectorSpec {
-                depth,
-                biome: Biome::Dungeon,
-                ..Default::default()
-            };
-            ret.insert(sector, spec);
+    /// Return whether you can travel from a sector to its neighbor in the given direction.
+    pub fn can_travel(&self, sector: Sector, dir: SectorDir) -> bool {
+        let (spec, neighbor) = match (
+            self.get(&sector),
+            self.get(&(sector + SectorVec::from(dir))),
+        ) {
+            (Some(spec), Some(neighbor)) => (spec, neighbor),
+            _ => return false,
+        };
+
+        if !dir.is_vertical()
+            && (spec.biome.is_underground() || neighbor.biome.is_underground())
+            && spec.biome != neighbor.biome
+        {
+            // Underground levels only extend sideways into sectors of the same biome.
+            return false;
         }
-        ret
-    }
 
-    pub fn overworld_sprawl() -> WorldSkeleton {
-        // FIXME
-        use calx::IntoPrefab;
-        const OVERWORLD_MAP: &str = "
-             ~ ~ ~ ~ ~ ~ ^ ^ ^ ^
-            ~ ~ ~ ~ . % % - - ^
-             ~ ~ . # . . % - - ^
-            ~ . . .[.]. . . - ^
-             ~ . . . . % . - - ^
-            ~ . . . . . . . . ^
-             ~ . . . # # . . . ^
-            ~ ~ . . # . . . . ^
-             ~ ~ . . . . . . . ^
-            ~ ~ ~ ~ ~ ~ ~ ^ ^ ^";
-
-        let map: HashMap<CellVector, Biome> = OVERWORLD_MAP
-            .into_prefab::<HashMap<CellVector, char>>()
-            .expect("Invalid overworld map")
-            .into_iter()
-            .map(|(p, c)| {
-                (
-                    p,
-                    match c {
-                        '~' => Biome::Water,
-                        '-' => Biome::Desert,
-                        '.' => Biome::Grassland,
-                        '%' => Biome::Forest,
-                        '#' => Biome::City,
-                        '^' => Biome::Mountain,
-                        _ => panic!("Unknown biome char {}", c),
-                    },
-                )
-            })
-            .collect();
+        match (spec.connection(dir), neighbor.connection(dir.opposite())) {
+            (Connection::Open, Connection::Open) => true,
+            (Connection::OneWay, Connection::Open) => dir.is_vertical(),
+            _ => false,
+        }
+    }
 
-        let mut ret = WorldSkeleton::default();
-        // Overworld
-        for (p, biome) in &map {
-            let depth = if *p == vec2(0, 0) {
-                // No spawns in entrance sector.
-                -1
-            } else {
-                (p.x.abs() + p.y.abs()) / 2
-            };
-            let sector = Sector::new(p.x as i16, p.y as i16, 0);
-            let spec = SectorSpec {
-                depth,
-                biome: *biome,
-            };
-            ret.insert(sector, spec);
+    /// Check that every sector can be reached from the player start sector.
+    pub fn validate(&self) -> Result<(), Box<dyn Error>> {
+        let mut seen = BTreeSet::new();
+        let mut open = vec![PLAYER_START_SECTOR];
+        if !self.contains_key(&PLAYER_START_SECTOR) {
+            die!("No player start sector {:?}", PLAYER_START_SECTOR);
         }
 
-        // Dungeons
-        for depth in 0..10 {
-            let sector = Sector::new(0, 0, -(depth as i16 + 1));
-            let spec = SectorSpec {
-                depth,
-                biome: Biome::Dungeon,
-                ..Default::default()
-            };
-            ret.insert(sector, spec);
+        while let Some(sector) = open.pop() {
+            if !seen.insert(sector) {
+                continue;
+            }
+            for &dir in SectorDir::iter() {
+                if self.can_travel(sector, dir) {
+                    open.push(sector + SectorVec::from(dir));
+                }
+            }
         }
 
-        ret
+        let mut unreachable: Vec<Sector> =
+            self.keys().filter(|s| !seen.contains(s)).cloned().collect();
+        if !unreachable.is_empty() {
+            unreachable.sort();
+            die!("Sectors unreachable from start: {:?}", unreachable);
+        }
+        Ok(())
     }
 }
 
@@ -602,9 +696,20 @@ impl<'a> Deref for ConnectedSectorSpec<'a> {
 
 impl<'a> Distribution<Map> for ConnectedSectorSpec<'a> {
     fn sample(&self, rng: &mut Rng) -> Map {
-        match self.biome {
-            Biome::Dungeon => self.build_dungeon(rng),
-            _ => self.build_biome_sample_map(rng),
+        if let Some(sector_map) = &self.map {
+            match self.build_predefined(rng, sector_map) {
+                Ok(map) => return map,
+                Err(e) => warn!(
+                    "Bad predefined map for {:?}, generating one instead: {}",
+                    self.sector, e
+                ),
+            }
+        }
+
+        if self.biome.is_underground() {
+            self.build_dungeon(rng)
+        } else {
+            self.build_biome_sample_map(rng)
         }
     }
 }
@@ -628,8 +733,6 @@ impl<'a> ConnectedSectorSpec<'a> {
     }
 
     fn dungeon_gen(&self, rng: &mut Rng) -> Result<Map, Box<dyn Error>> {
-        // TODO: Connect to side levels if they exist
-
         debug!("Starting mapgen");
         let mut map = self.dungeon_base_map();
 
@@ -643,7 +746,10 @@ impl<'a> ConnectedSectorSpec<'a> {
             }
         }
 
-        if let Some(map) = map.join_disjoint_regions(rng) {
+        self.open_crossings(&mut map)?;
+
+        if let Some(mut map) = map.join_disjoint_regions(rng) {
+            self.place_treasure(rng, &mut map);
             Ok(map)
         } else {
             die!("Failed to join map");
@@ -654,15 +760,133 @@ impl<'a> ConnectedSectorSpec<'a> {
         self.skeleton.get(&(self.sector + offset.into()))
     }
 
+    /// Return whether there's a passage to the neighbor in the given direction.
+    ///
+    /// Includes one-way passages in either direction, so that both ends of a one-way stairwell
+    /// get generated.
+    fn connects(&self, dir: SectorDir) -> bool {
+        self.skeleton.can_travel(self.sector, dir)
+            || self
+                .skeleton
+                .can_travel(self.sector + SectorVec::from(dir), dir.opposite())
+    }
+
+    /// Block off the edges that border neighboring sectors this sector doesn't connect to.
+    fn seal_edges(&self, map: &mut Map) {
+        let sealed: Vec<Vector2D<i32, SectorSpace>> = SectorDir::iter()
+            .filter(|&&dir| {
+                !dir.is_vertical() && self.neighbor(dir).is_some() && !self.connects(dir)
+            })
+            .map(|&dir| SectorVec::from(dir).xy().cast())
+            .collect();
+        if sealed.is_empty() {
+            return;
+        }
+
+        for p in map.find_positions(|_, _| true) {
+            if calx::hex_neighbors(p).any(|n| sealed.contains(&n.project::<SectorSpace>())) {
+                map.set_terrain(p, Terrain::Rock);
+            }
+        }
+    }
+
+    /// Return the cell where a level spanning several sectors crosses into the neighboring
+    /// sector in the given direction.
+    ///
+    /// The crossing only depends on the seed and the sector pair, so the sectors on both sides
+    /// agree on it without either needing the other to be generated. Returns `None` if the
+    /// sectors have no stretch of border to cross.
+    fn crossing(&self, dir: SectorDir) -> Option<CellVector> {
+        // Stairwell vaults get built around the stairs, don't tunnel into them.
+        const STAIRWELL_CLEARANCE: i32 = 3;
+
+        let other = self.sector + SectorVec::from(dir);
+        let (a, b) = if self.sector < other {
+            (self.sector, other)
+        } else {
+            (other, self.sector)
+        };
+        let b_vec = vec2((b.x - a.x) as i32, (b.y - a.y) as i32);
+        let b_offset = Location::from(a).v2_at(Location::from(b)).unwrap();
+
+        // Cells that are on the sector's map and clear of its stairwells.
+        let usable = |sector: Sector| -> HashSet<CellVector> {
+            let spec = ConnectedSectorSpec::new(self.seed, sector, self.skeleton);
+            let stairs: Vec<CellVector> = spec
+                .upstairs_pos()
+                .into_iter()
+                .chain(spec.downstairs_pos())
+                .collect();
+            spec.base_shape()
+                .filter(|&p| {
+                    stairs
+                        .iter()
+                        .all(|&s| (p - s).hex_dist() > STAIRWELL_CLEARANCE)
+                })
+                .collect()
+        };
+        let (usable_a, usable_b) = (usable(a), usable(b));
+        // Stay clear of the corners where three sectors meet.
+        let off_corner = |p: CellVector| {
+            calx::hex_neighbors(p).all(|n| {
+                let s = n.project::<SectorSpace>();
+                s == vec2(0, 0) || s == b_vec
+            })
+        };
+
+        // Adjacent cell pairs across the border in the frame of sector a.
+        let mut pairs = Vec::new();
+        for p in Sector::shape() {
+            for q in calx::hex_neighbors(p) {
+                if q.project::<SectorSpace>() == b_vec
+                    && usable_a.contains(&p)
+                    && usable_b.contains(&(q - b_offset))
+                    && off_corner(p)
+                    && off_corner(q)
+                {
+                    pairs.push((p, q));
+                }
+            }
+        }
+
+        let mut rng = seeded_rng(&(self.seed, a, b));
+        let &(p, q) = pairs.choose(&mut rng)?;
+        if self.sector == a {
+            Some(p)
+        } else {
+            Some(q - b_offset)
+        }
+    }
+
+    /// Dig open the crossings to the sideways neighbors that continue the level.
+    fn open_crossings(&self, map: &mut Map) -> Result<(), Box<dyn Error>> {
+        for &dir in SectorDir::iter() {
+            if dir.is_vertical() || !self.connects(dir) {
+                continue;
+            }
+            let pos = match self.crossing(dir) {
+                Some(pos) => pos,
+                None => die!("No room for a crossing to {:?}", dir),
+            };
+            if map.get(pos).is_some_and(|c| c.can_dig()) {
+                map.dig(pos);
+            }
+            if !map.get(pos).is_some_and(|c| c.is_walkable()) {
+                die!("Crossing to {:?} is blocked", dir);
+            }
+        }
+        Ok(())
+    }
+
     fn place_stairs(&self, rng: &mut Rng, map: &mut Map) -> Result<(), Box<dyn Error>> {
         // TODO: Biome affects vault distribution
-        if self.neighbor(SectorDir::Up).is_some() {
+        if self.connects(SectorDir::Up) {
             let room: Entrance = self.sample(rng);
             debug!("Placing upstairs");
             map.place_room(rng, &*room.0)?;
         }
 
-        if self.neighbor(SectorDir::Down).is_some() {
+        if self.connects(SectorDir::Down) {
             // TODO: Make exit use a sampled type like Entrance does
             debug!("Placing downstairs");
             let room = vaults::EXITS.choose(rng).unwrap();
@@ -671,16 +895,35 @@ impl<'a> ConnectedSectorSpec<'a> {
         Ok(())
     }
 
-    fn build_dungeon(&self, rng: &mut Rng) -> Map {
+    /// Roll the contents of treasure spots in vaults.
+    fn place_treasure(&self, rng: &mut Rng, map: &mut Map) {
+        for pos in map.find_positions(|_, c| c.treasure) {
+            if let Some(spawn) = spec::TREASURE.roll(rng, self.depth) {
+                map.push_spawn(pos, spawn);
+            }
+        }
+    }
+
+    /// Try to generate a dungeon map a few times before giving up.
+    fn retry_dungeon_gen(&self, rng: &mut Rng) -> Result<Map, Box<dyn Error>> {
         const NUM_RETRIES: usize = 16;
+        calx::retry_gen(NUM_RETRIES, rng, |rng| self.dungeon_gen(rng))
+    }
 
-        if let Ok(map) = calx::retry_gen(NUM_RETRIES, rng, |rng| self.dungeon_gen(rng)) {
+    fn build_dungeon(&self, rng: &mut Rng) -> Map {
+        let mut map = if let Ok(map) = self.retry_dungeon_gen(rng) {
             map
         } else {
             // Fallback, couldn't generate map, let's do something foolproof.
             warn!("Repeated dungeon generation failure, falling back to bigroom");
             self.build_bigroom(rng)
+        };
+
+        for pos in map.find_positions(|_, _| true) {
+            let terrain = self.biome.dungeon_terrain(rng, map[pos].terrain);
+            map.set_terrain(pos, terrain);
         }
+        map
     }
 
     fn build_bigroom(&self, rng: &mut Rng) -> Map {
@@ -688,7 +931,8 @@ impl<'a> ConnectedSectorSpec<'a> {
 
         self.place_stairs(rng, &mut map).unwrap();
 
-        for p in map.find_positions(|_, _| true) {
+        // Stairwell vaults can leave undiggable cells around.
+        for p in map.find_positions(|_, c| c.can_dig()) {
             map.dig(p);
         }
 
@@ -702,22 +946,26 @@ impl<'a> ConnectedSectorSpec<'a> {
     }
 
     fn downstairs_pos(&self) -> Option<CellVector> {
-        self.neighbor(SectorDir::Down).map(|_| {
+        if !self.connects(SectorDir::Down) {
+            return None;
+        }
+        Some(
             Location::from(self.sector)
                 .v2_at(self.sector.downstairs_location(self.seed))
-                .unwrap()
-        })
+                .unwrap(),
+        )
     }
 
     fn upstairs_pos(&self) -> Option<CellVector> {
-        self.neighbor(SectorDir::Up).map(|_| {
-            let mut upstairs_pos = (self.sector + vec3(0, 0, 1)).downstairs_location(self.seed);
-            upstairs_pos.z -= 1;
-            // Offset it so that the exits line up nicer.
-            upstairs_pos.x -= 1;
-            upstairs_pos.y -= 1;
-            Location::from(self.sector).v2_at(upstairs_pos).unwrap()
-        })
+        if !self.connects(SectorDir::Up) {
+            return None;
+        }
+        let mut upstairs_pos = (self.sector + vec3(0, 0, 1)).downstairs_location(self.seed);
+        upstairs_pos.z -= 1;
+        // Offset it so that the exits line up nicer.
+        upstairs_pos.x -= 1;
+        upstairs_pos.y -= 1;
+        Some(Location::from(self.sector).v2_at(upstairs_pos).unwrap())
     }
 
     fn place_stairwells(&self, map: &mut Map) {
@@ -738,6 +986,68 @@ impl<'a> ConnectedSectorSpec<'a> {
         ret
     }
 
+    fn build_predefined(
+        &self,
+        rng: &mut Rng,
+        sector_map: &SectorMap,
+    ) -> Result<Map, Box<dyn Error>> {
+        let (offset, authored) = sector_map.build(self.sector)?;
+
+        let origin = Location::from(self.sector);
+        let mut map = Map::default();
+        for p in self.base_shape() {
+            let terrain = self.biome.terrain_at(self.seed, origin + p);
+            map.insert(p, MapCell::new_terrain(terrain));
+        }
+        self.seal_edges(&mut map);
+        map.paste(offset, &authored);
+
+        self.stitch_stairs(&mut map);
+        self.place_treasure(rng, &mut map);
+
+        if self.biome.is_underground() {
+            // Dungeon base is solid rock, tunnel the authored parts, the stairs and the crossings
+            // to the rest of the level together.
+            self.open_crossings(&mut map)?;
+            match map.join_disjoint_regions(rng) {
+                Some(map) => Ok(map),
+                None => die!("Failed to join map"),
+            }
+        } else {
+            Ok(map)
+        }
+    }
+
+    /// Make the stairs of a predefined map match the neighboring sectors.
+    ///
+    /// Stairs that lead nowhere are removed and missing stairs are added in the default
+    /// stairwell positions.
+    fn stitch_stairs(&self, map: &mut Map) {
+        let (up, down) = (self.upstairs_pos(), self.downstairs_pos());
+
+        for (stairs, pos, landing) in &[
+            (Terrain::Upstairs, up, vec2(1, 1)),
+            (Terrain::Downstairs, down, vec2(-1, -1)),
+        ] {
+            let existing = map.find_positions(|_, c| c.terrain == *stairs);
+            match pos {
+                None => {
+                    for p in existing {
+                        map.set_terrain(p, Terrain::Ground);
+                    }
+                }
+                Some(pos) if existing.is_empty() => {
+                    map.set_terrain(*pos, *stairs);
+                    // Make sure there's somewhere to stand when arriving via the stairs.
+                    if map.get(*pos + *landing).is_some_and(|c| !c.is_walkable()) {
+                        map.set_terrain(*pos + *landing, Terrain::Ground);
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
     fn build_biome_sample_map(&self, rng: &mut Rng) -> Map {
         let mut map = Map::default();
         for p in self.base_shape() {
@@ -757,6 +1067,7 @@ impl<'a> ConnectedSectorSpec<'a> {
         }
 
         // TODO: Add enclosures
+        self.seal_edges(&mut map);
         self.place_stairwells(&mut map);
 
         for &pos in &map.open_ground() {
@@ -774,42 +1085,31 @@ impl<'a> ConnectedSectorSpec<'a> {
     }
 }
 
-impl Distribution<EntitySpawn> for ConnectedSectorSpec<'_> {
-    fn sample(&self, rng: &mut Rng) -> EntitySpawn {
-        let item = spec::iter_specs()
-            .weighted_choice(rng, |item| {
-                if item.rarity() == 0.0 || !self.can_spawn(&**item) {
-                    0.0
-                } else {
-                    1.0 / item.rarity()
-                }
-            })
-            .unwrap();
-
-        EntitySpawn::from_str(item.id()).unwrap()
-    }
-}
-
 /// Include spawn density of entities, can be run over all open cells.
 ///
 /// XXX: You maybe want something smarter than this to handle clustering of mobs etc.
 impl Distribution<Option<EntitySpawn>> for ConnectedSectorSpec<'_> {
     fn sample(&self, rng: &mut Rng) -> Option<EntitySpawn> {
-        use Biome::*;
         if self.depth == -1 {
             return None;
         }
 
-        let spawn_one_in = match self.biome {
-            Dungeon => 10,
-            _ => 100,
-        };
+        let spawn_one_in = if self.biome.is_underground() { 10 } else { 100 };
 
-        if rng.one_chance_in(spawn_one_in) {
-            Some(self.sample(rng))
-        } else {
-            None
+        if !rng.one_chance_in(spawn_one_in) {
+            return None;
         }
+
+        // Some biomes, like open water, have nothing that can live in them.
+        let item = spec::iter_specs().weighted_choice(rng, |item| {
+            if item.rarity() == 0.0 || !self.can_spawn(&**item) {
+                0.0
+            } else {
+                1.0 / item.rarity()
+            }
+        })?;
+
+        Some(EntitySpawn::from_str(item.id()).unwrap())
     }
 }
 
@@ -827,7 +1127,13 @@ impl Distribution<Room> for ConnectedSectorSpec<'_> {
     fn sample(&self, rng: &mut Rng) -> Room {
         if rng.one_chance_in(12) {
             // Make a vault sometimes.
-            Room(vaults::VAULTS.choose(rng).unwrap().clone())
+            let vaults = match self.biome {
+                Biome::Sewer => &*vaults::SEWER_VAULTS,
+                Biome::Cave => &*vaults::CAVE_VAULTS,
+                Biome::Tomb => &*vaults::TOMB_VAULTS,
+                _ => &*vaults::VAULTS,
+            };
+            Room(vaults.choose(rng).unwrap().clone())
         } else {
             // Make a procgen room normally.
             let mut map = Map::new_plain_room(rng);
@@ -902,6 +1208,184 @@ mod test {
         }
     }
 
+    #[test]
+    fn test_predefined_sector() {
+        use super::{generate, SectorMap, SectorSpec, WorldSkeleton};
+        use crate::{spec::EntitySpawn, Terrain};
+        use std::str::FromStr;
+
+        // The boss lair keeps its own stairs and spawns.
+        let bottom = Sector::new(0, 0, -9);
+        let map = generate(1, bottom, &WorldSkeleton::dungeon_dive());
+        assert_eq!(map.upstairses(), vec![vec2(14, -4)]);
+        assert!(map.downstairses().is_empty());
+        assert_eq!(
+            map[vec2(19, 0)].spawns,
+            vec![EntitySpawn::from_str("moloch").unwrap()]
+        );
+
+        // A map without stairs gets them from the neighboring sectors.
+        let mut skeleton = WorldSkeleton::default();
+        let vault = "\
+#######
+#.....#
+#.....#
+#######";
+        for z in -1..=1 {
+            skeleton.insert(
+                Sector::new(0, 0, z),
+                SectorSpec {
+                    map: Some(SectorMap::Vault(vault.to_string())),
+                    ..Default::default()
+                },
+            );
+        }
+        let map = generate(1, Sector::new(0, 0, 0), &skeleton);
+        assert_eq!(map.upstairses().len(), 1);
+        assert_eq!(map.downstairses().len(), 1);
+        assert_eq!(map[vec2(17, -1)].terrain, Terrain::Ground);
+
+        // No stairs up from the top floor.
+        let map = generate(1, Sector::new(0, 0, 1), &skeleton);
+        assert!(map.upstairses().is_empty());
+        assert_eq!(map.downstairses().len(), 1);
+    }
+
+    #[test]
+    fn test_connections() {
+        use super::{generate, Biome, Connection, SectorDir, SectorSpec, WorldSkeleton};
+        use crate::{world_cache::WorldCache, Terrain};
+
+        let dive = |connection| {
+            let mut skeleton = WorldSkeleton::dungeon_dive();
+            let spec = skeleton.get_mut(&Sector::new(0, 0, -1)).unwrap();
+            spec.connections.insert(SectorDir::Down, connection);
+            skeleton
+        };
+
+        assert!(WorldSkeleton::dungeon_dive().validate().is_ok());
+        assert!(WorldSkeleton::overworld_sprawl().validate().is_ok());
+        assert!(dive(Connection::Sealed).validate().is_err());
+
+        // One-way drops can't be climbed back up.
+        let skeleton = dive(Connection::OneWay);
+        assert!(skeleton.validate().is_ok());
+        assert!(skeleton.can_travel(Sector::new(0, 0, -1), SectorDir::Down));
+        assert!(!skeleton.can_travel(Sector::new(0, 0, -2), SectorDir::Up));
+
+        let cache = WorldCache::new(1, skeleton);
+        let find =
+            |sector: Sector, terrain| sector.iter().find(|&loc| cache.get_terrain(loc) == terrain);
+        let down = find(Sector::new(0, 0, -1), Terrain::Downstairs).unwrap();
+        let landing = cache.get_portal(down).unwrap();
+        assert_eq!(Sector::from(landing), Sector::new(0, 0, -2));
+        assert!(!cache.get_terrain(landing).blocks_walk());
+        assert!(find(Sector::new(0, 0, -2), Terrain::Upstairs).is_none());
+
+        // Sealed overland borders get walled off.
+        let mut skeleton = WorldSkeleton::default();
+        for x in 0..2 {
+            skeleton.insert(
+                Sector::new(x, 0, 0),
+                SectorSpec {
+                    biome: Biome::Grassland,
+                    ..Default::default()
+                },
+            );
+        }
+        assert!(skeleton.can_travel(Sector::new(0, 0, 0), SectorDir::East));
+        skeleton
+            .get_mut(&Sector::new(1, 0, 0))
+            .unwrap()
+            .connections
+            .insert(SectorDir::West, Connection::Sealed);
+        assert!(!skeleton.can_travel(Sector::new(0, 0, 0), SectorDir::East));
+        assert!(skeleton.validate().is_err());
+
+        let map = generate(1, Sector::new(0, 0, 0), &skeleton);
+        let east = map.find_positions(|p, _| {
+            calx::hex_neighbors(p).any(|n| n.project::<super::SectorSpace>() == vec2(1, 0))
+        });
+        assert!(!east.is_empty());
+        assert!(east.iter().all(|&p| map[p].terrain == Terrain::Rock));
+    }
+
+    #[test]
+    fn test_dungeon_branches() {
+        use super::{generate, Biome, SectorDir, WorldSkeleton};
+        use crate::Terrain;
+
+        let skeleton = WorldSkeleton::overworld_sprawl();
+        let has = |sector, terrain| {
+            let map = generate(1, sector, &skeleton);
+            !map.find_positions(|_, c| c.terrain == terrain).is_empty()
+        };
+
+        let sewer = Sector::new(-1, -1, -1);
+        assert_eq!(skeleton[&sewer].biome, Biome::Sewer);
+        assert!(skeleton.can_travel(sewer, SectorDir::Up));
+        assert!(has(sewer, Terrain::Shallows));
+
+        let cave = Sector::new(2, 1, -1);
+        assert!(!has(cave, Terrain::Wall));
+        assert!(!has(cave, Terrain::Door));
+
+        let tomb = Sector::new(4, 0, -2);
+        assert!(has(tomb, Terrain::Sand));
+        assert!(!has(tomb, Terrain::Ground));
+    }
+
+    #[test]
+    fn test_wide_levels() {
+        use super::{generate, ConnectedSectorSpec, SectorDir, SectorVec, WorldSkeleton};
+        use crate::Location;
+
+        let skeleton = WorldSkeleton::overworld_sprawl();
+        // Branches next to the main dungeon stay separate.
+        assert!(!skeleton.can_travel(Sector::new(0, 0, -1), SectorDir::Northwest));
+        // Only the stack under the top sector has stairs.
+        assert!(skeleton.can_travel(Sector::new(0, 0, -6), SectorDir::Down));
+        assert!(!skeleton.can_travel(Sector::new(1, 0, -6), SectorDir::Up));
+        assert!(!skeleton.can_travel(Sector::new(1, 0, -7), SectorDir::Up));
+        assert!(!skeleton.can_travel(Sector::new(1, 0, -7), SectorDir::Down));
+
+        let top = Sector::new(0, 0, -6);
+        for &dir in &[SectorDir::East, SectorDir::Southeast, SectorDir::Southwest] {
+            assert!(skeleton.can_travel(top, dir));
+
+            // Both sides agree on where the level crosses the border and leave it open.
+            let ends: Vec<Location> = [(top, dir), (top + SectorVec::from(dir), dir.opposite())]
+                .iter()
+                .map(|&(sector, dir)| {
+                    let pos = ConnectedSectorSpec::new(1, sector, &skeleton)
+                        .crossing(dir)
+                        .unwrap();
+                    assert!(generate(1, sector, &skeleton)[pos].is_walkable());
+                    Location::from(sector) + pos
+                })
+                .collect();
+            assert_eq!(ends[0].distance_from(ends[1]), Some(1));
+            assert_eq!(Sector::from(ends[1]), top + SectorVec::from(dir));
+        }
+
+        // The wide levels generate properly instead of falling back to a big room.
+        let wide: Vec<Sector> = skeleton
+            .keys()
+            .filter(|s| s.z <= -6 && s.z > -11)
+            .cloned()
+            .collect();
+        assert_eq!(wide.len(), 20);
+        for seed in 1..=5 {
+            for &sector in &wide {
+                let spec = ConnectedSectorSpec::new(seed, sector, &skeleton);
+                let mut rng = calx::seeded_rng(&(seed, sector));
+                if let Err(e) = spec.retry_dungeon_gen(&mut rng) {
+                    panic!("Seed {} sector {:?} failed: {}", seed, sector, e);
+                }
+            }
+        }
+    }
+
     #[test]
     fn test_sector_shape() {
         assert_eq!(
@@ -0,0 +1,355 @@
+//! Data file formats for world skeletons
+
+use crate::{
+    location::Location,
+    mapsave::{MapPatch, PatchData, WorldData},
+    sector::{Biome, Connection, Sector, SectorDir, SectorMap, SectorSpec, WorldSkeleton},
+    spec::PLAYER_SPAWN,
+    terrain::Terrain,
+    world_cache::PLAYER_START_SECTOR,
+};
+use calx::{die, tiled, CellVector, IntoPrefab};
+use euclid::vec3;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::convert::TryFrom;
+use std::error::Error;
+use std::fs;
+use std::path::Path;
+use std::str::FromStr;
+
+/// Hand-editable description of a world skeleton.
+#[derive(Clone, Default, Debug, Serialize, Deserialize)]
+pub struct SkeletonData {
+    /// Surface biome map with one character per sector, see `Biome::from_char`.
+    ///
+    /// The origin sector is marked with brackets.
+    #[serde(default)]
+    pub overworld: String,
+    /// Stacks of dungeon floors.
+    #[serde(default)]
+    pub dungeons: Vec<DungeonData>,
+    /// Individually specified sectors, these replace ones from the overworld and the dungeons.
+    #[serde(default)]
+    pub sectors: BTreeMap<Sector, SectorSpec>,
+}
+
+/// Stack of dungeon floors going down from the top sector.
+///
+/// Floors can span several sectors, but only the stack of sectors under `top` has stairs.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct DungeonData {
+    pub top: Sector,
+    pub floors: i32,
+    /// Underground biome of the floors.
+    #[serde(default)]
+    pub biome: Biome,
+    /// Depth of the top floor.
+    #[serde(default)]
+    pub depth: i32,
+    /// Number of sectors each floor spans eastward from the stack.
+    #[serde(default = "one")]
+    pub width: i16,
+    /// Number of sectors each floor spans southwestward from the stack.
+    #[serde(default = "one")]
+    pub height: i16,
+}
+
+fn one() -> i16 { 1 }
+
+impl Biome {
+    pub fn from_char(c: char) -> Option<Biome> {
+        use Biome::*;
+        match c {
+            '~' => Some(Water),
+            '-' => Some(Desert),
+            '.' => Some(Grassland),
+            '%' => Some(Forest),
+            '#' => Some(City),
+            '^' => Some(Mountain),
+            _ => None,
+        }
+    }
+}
+
+impl WorldSkeleton {
+    pub fn dungeon_dive() -> WorldSkeleton {
+        WorldSkeleton::from_str(include_str!("../assets/dungeon_dive.ron"))
+            .expect("Invalid built-in world")
+    }
+
+    pub fn overworld_sprawl() -> WorldSkeleton {
+        WorldSkeleton::from_str(include_str!("../assets/overworld_sprawl.ron"))
+            .expect("Invalid built-in world")
+    }
+
+    /// Load a world skeleton from a RON skeleton data file or a Tiled JSON map.
+    pub fn load(path: impl AsRef<Path>) -> Result<WorldSkeleton, Box<dyn Error>> {
+        let path = path.as_ref();
+        let text = fs::read_to_string(path)?;
+        if path.extension().is_some_and(|e| e == "json") {
+            let map: tiled::Map = serde_json::from_str(&text)?;
+            WorldSkeleton::try_from(WorldData::try_from(map)?)
+        } else {
+            WorldSkeleton::from_str(&text)
+        }
+    }
+
+    /// Check that the skeleton is playable and its predefined maps are valid.
+    fn check(self) -> Result<WorldSkeleton, Box<dyn Error>> {
+        for (sector, spec) in self.iter() {
+            if let Some(map) = &spec.map {
+                map.build(*sector)
+                    .map_err(|e| format!("Bad map in sector {:?}: {}", sector, e))?;
+            }
+        }
+        self.validate()?;
+        Ok(self)
+    }
+}
+
+impl FromStr for WorldSkeleton {
+    type Err = Box<dyn Error>;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let data: SkeletonData = ron::de::from_str(s)?;
+        WorldSkeleton::try_from(data)
+    }
+}
+
+impl TryFrom<SkeletonData> for WorldSkeleton {
+    type Error = Box<dyn Error>;
+
+    fn try_from(data: SkeletonData) -> Result<Self, Self::Error> {
+        let mut ret = WorldSkeleton::default();
+
+        if !data.overworld.trim().is_empty() {
+            let map: HashMap<CellVector, char> = data
+                .overworld
+                .into_prefab()
+                .map_err(|e| format!("Bad overworld map: {}", e))?;
+            let mut cells: Vec<(CellVector, char)> = map.into_iter().collect();
+            cells.sort_by_key(|(p, _)| (p.y, p.x));
+
+            for (p, c) in cells {
+                let sector = Sector::new(p.x as i16, p.y as i16, 0);
+                let biome = Biome::from_char(c)
+                    .ok_or_else(|| format!("Unknown biome char {:?} in sector {:?}", c, sector))?;
+                let spec = SectorSpec {
+                    depth: default_depth(sector),
+                    biome,
+                    ..Default::default()
+                };
+                ret.insert(sector, spec);
+            }
+        }
+
+        for dungeon in &data.dungeons {
+            if !dungeon.biome.is_underground() {
+                die!(
+                    "Dungeon at {:?} has surface biome {:?}",
+                    dungeon.top,
+                    dungeon.biome
+                );
+            }
+            for floor in 0..dungeon.floors {
+                for y in 0..dungeon.height {
+                    for x in 0..dungeon.width {
+                        let mut spec = SectorSpec {
+                            depth: dungeon.depth + floor,
+                            biome: dungeon.biome,
+                            ..Default::default()
+                        };
+                        if (x, y) != (0, 0) {
+                            // Floors are entered through the stack, the rest of the floor is
+                            // reached sideways from there.
+                            spec.connections.insert(SectorDir::Up, Connection::Sealed);
+                            spec.connections.insert(SectorDir::Down, Connection::Sealed);
+                        }
+                        ret.insert(dungeon.top + vec3(x, y, -(floor as i16)), spec);
+                    }
+                }
+            }
+        }
+
+        ret.extend(data.sectors);
+        ret.check()
+    }
+}
+
+impl TryFrom<WorldData> for WorldSkeleton {
+    type Error = Box<dyn Error>;
+
+    /// Make a skeleton of predefined sectors from map data.
+    ///
+    /// The map is moved so that the sector with the player spawn, or failing that the first
+    /// sector, ends up as the player start sector.
+    fn try_from(data: WorldData) -> Result<Self, Self::Error> {
+        let has_player = |patch: &&PatchData| {
+            patch
+                .patch
+                .iter()
+                .any(|(_, (_, spawns))| spawns.contains(&*PLAYER_SPAWN))
+        };
+        let start = data
+            .patches
+            .iter()
+            .find(has_player)
+            .or_else(|| data.patches.first())
+            .ok_or("Empty map")?;
+        let origin = Location::from(Sector::from(start.offset));
+
+        let mut ret = WorldSkeleton::default();
+        for patch in data.patches {
+            let offset = Location::new(
+                patch.offset.x - origin.x,
+                patch.offset.y - origin.y,
+                patch.offset.z - origin.z,
+            );
+            let sector = Sector::from(offset);
+            let spec = SectorSpec {
+                depth: default_depth(sector),
+                biome: guess_biome(sector, &patch.patch),
+                map: Some(SectorMap::Patch {
+                    offset,
+                    patch: patch.patch,
+                }),
+                ..Default::default()
+            };
+            ret.insert(sector, spec);
+        }
+        ret.fill_surface_gaps();
+
+        ret.check()
+    }
+}
+
+impl WorldSkeleton {
+    /// Fill the gaps between the surface sectors with generated sectors.
+    ///
+    /// Maps can be a loose collection of separate sector-sized pieces. A filler sector gets the
+    /// biome of the nearest existing surface sector.
+    fn fill_surface_gaps(&mut self) {
+        let surface: Vec<(Sector, Biome)> = self
+            .iter()
+            .filter(|(s, _)| s.z == 0)
+            .map(|(&s, spec)| (s, spec.biome))
+            .collect();
+        if surface.is_empty() {
+            return;
+        }
+        let (x0, x1) = (
+            surface.iter().map(|(s, _)| s.x).min().unwrap(),
+            surface.iter().map(|(s, _)| s.x).max().unwrap(),
+        );
+        let (y0, y1) = (
+            surface.iter().map(|(s, _)| s.y).min().unwrap(),
+            surface.iter().map(|(s, _)| s.y).max().unwrap(),
+        );
+
+        for y in y0..=y1 {
+            for x in x0..=x1 {
+                let sector = Sector::new(x, y, 0);
+                if self.contains_key(&sector) {
+                    continue;
+                }
+                let distance = |s: &Sector| (s.x - x).abs() as i32 + (s.y - y).abs() as i32;
+                let biome = surface
+                    .iter()
+                    .min_by_key(|(s, _)| (distance(s), *s))
+                    .map(|&(_, b)| b)
+                    .unwrap();
+                self.insert(
+                    sector,
+                    SectorSpec {
+                        depth: default_depth(sector),
+                        biome,
+                        ..Default::default()
+                    },
+                );
+            }
+        }
+    }
+}
+
+/// Danger level grows with the distance from the start, the start sector has no spawns.
+fn default_depth(sector: Sector) -> i32 {
+    if sector == PLAYER_START_SECTOR {
+        -1
+    } else {
+        (sector.x.abs() as i32 + sector.y.abs() as i32) / 2 + (-sector.z as i32).max(0)
+    }
+}
+
+/// Guess the biome of a hand-made map from its most common terrain.
+fn guess_biome(sector: Sector, patch: &MapPatch) -> Biome {
+    use Terrain::*;
+
+    if sector.z < 0 {
+        return Biome::Dungeon;
+    }
+
+    let mut counts = BTreeMap::new();
+    for (_, (terrain, _)) in patch.iter() {
+        *counts.entry(terrain).or_insert(0) += 1;
+    }
+
+    match counts.into_iter().max_by_key(|&(_, n)| n).map(|(t, _)| t) {
+        Some(Water) | Some(Shallows) => Biome::Water,
+        Some(Tree) | Some(DeadTree) => Biome::Forest,
+        Some(Sand) => Biome::Desert,
+        Some(Rock) => Biome::Mountain,
+        Some(Wall) | Some(Door) | Some(Window) => Biome::City,
+        _ => Biome::Grassland,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::SkeletonData;
+    use crate::{Biome, Sector, WorldSkeleton};
+    use std::convert::TryFrom;
+    use std::str::FromStr;
+
+    #[test]
+    fn test_skeleton_data() {
+        let skeleton = WorldSkeleton::overworld_sprawl();
+        assert_eq!(skeleton[&Sector::new(0, 0, 0)].depth, -1);
+        assert_eq!(skeleton[&Sector::new(0, 0, -1)].biome, Biome::Dungeon);
+        assert_eq!(skeleton[&Sector::new(-1, -1, -1)].biome, Biome::Sewer);
+        assert!(WorldSkeleton::dungeon_dive()[&Sector::new(0, 0, -9)]
+            .map
+            .is_some());
+
+        // Errors point at the broken sector.
+        let err = WorldSkeleton::from_str(r#"(overworld: "[.]x")"#)
+            .err()
+            .unwrap()
+            .to_string();
+        assert!(err.contains("'x'") && err.contains("Sector {"), "{}", err);
+
+        // Unreachable sectors are an error.
+        let data = SkeletonData {
+            overworld: "[.]".into(),
+            dungeons: vec![super::DungeonData {
+                top: Sector::new(5, 5, -1),
+                floors: 2,
+                biome: Biome::Dungeon,
+                depth: 0,
+                width: 1,
+                height: 1,
+            }],
+            ..Default::default()
+        };
+        assert!(WorldSkeleton::try_from(data).is_err());
+    }
+
+    #[test]
+    fn test_load_tiled() {
+        for file in &["overworld-base.json", "test-world.json"] {
+            let path = format!("{}/../tiled/{}", env!("CARGO_MANIFEST_DIR"), file);
+            let skeleton = WorldSkeleton::load(&path).unwrap();
+            assert!(skeleton.values().any(|spec| spec.map.is_some()));
+        }
+    }
+}
@@ -61,7 +61,7 @@ impl World {
 
 /// Entities can be placed either on open locations or inside other entities.
 /// A sum type will represent this nicely.
-#[derive(Copy, Eq, PartialEq, Clone, PartialOrd, Ord, Debug, Serialize, Deserialize)]
+#[derive(Copy, Eq, PartialEq, Hash, Clone, PartialOrd, Ord, Debug, Serialize, Deserialize)]
 pub enum Place {
     At(Location),
     // XXX: Implementation can store multiple entities in a single Slot-place, but in practice
@@ -72,7 +72,7 @@ pub enum Place {
 }
 
 /// Spatial index for game entities
-#[derive(Default)]
+#[derive(Default, Hash)]
 pub struct Spatial {
     place_to_entities: BTreeMap<Place, Vec<Entity>>,
     entity_to_place: BTreeMap<Entity, Place>,
@@ -236,10 +236,15 @@ impl Spatial {
     pub fn get(&self, e: Entity) -> Option<Place> { self.entity_to_place.get(&e).cloned() }
 
     /// Flatten to an easily serializable vector.
+    ///
+    /// Entities sharing a place are listed in their stacking order so that the order survives
+    /// serialization.
     fn dump(&self) -> Vec<Elt> {
         let mut ret = vec![];
-        for (&e, &loc) in &self.entity_to_place {
-            ret.push(Elt(e, loc));
+        for (&loc, es) in &self.place_to_entities {
+            for &e in es {
+                ret.push(Elt(e, loc));
+            }
         }
         ret
     }
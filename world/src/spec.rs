@@ -1,18 +1,22 @@
 //! Data for generating game entities.
 
 use crate::{
-    ai::{Brain, ShoutType},
+    ai::{Alignment, Brain, ShoutType},
+    death::DeathEffect,
     desc::{Desc, Icon},
+    effect::{Ability, AbilitySpec, Damage, Effect, Targeting},
     item::ItemType,
-    item::{Item, Stacking},
+    item::{Item, Requirements, Stacking},
+    noise::EXPLOSION_LOUDNESS,
     sector::Biome,
     stats::{Health, Intrinsic, Stats, StatsComponent, Statuses},
     world::Loadout,
-    Anim, Distribution, ExternalEntity, Rng,
+    Anim, AnimState, Distribution, ExternalEntity, LootTable, Rng, Terrain,
 };
 use lazy_static::lazy_static;
+use rand::Rng as _;
 use serde;
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, HashMap};
 use std::error::Error;
 use std::fmt;
 use std::str::FromStr;
@@ -51,6 +55,10 @@ const DUNGEON: u64 = 1 << Biome::Dungeon as u64;
 const TEMPERATE: u64 = (1 << Biome::Grassland as u64) | (1 << Biome::Forest as u64);
 const ARID: u64 = (1 << Biome::Desert as u64) | (1 << Biome::Mountain as u64);
 const URBAN: u64 = 1 << Biome::City as u64;
+const SEWER: u64 = 1 << Biome::Sewer as u64;
+const CAVE: u64 = 1 << Biome::Cave as u64;
+const TOMB: u64 = 1 << Biome::Tomb as u64;
+const UNDERGROUND: u64 = DUNGEON | SEWER | CAVE | TOMB;
 
 #[derive(Debug)]
 pub struct MobSpec {
@@ -61,7 +69,21 @@ pub struct MobSpec {
     habitat: u64,
     power: i32,
     intrinsics: Vec<Intrinsic>,
+    /// Resistances (positive) and vulnerabilities (negative) to damage types, in percent.
+    resistances: Vec<(Damage, i32)>,
+    /// Range of natural ranged attack, zero for no ranged attack.
+    ranged_range: u32,
+    ranged_power: i32,
+    /// Size of mana pool for casting spells.
+    mana: i32,
+    /// Spells the mob can cast with mana.
+    spells: Vec<Ability>,
+    /// What happens when the mob dies.
+    on_death: Vec<DeathEffect>,
+    /// Items the mob may drop when it dies.
+    loot: LootTable,
     shout: ShoutType,
+    alignment: Alignment,
 }
 
 impl Default for MobSpec {
@@ -74,25 +96,46 @@ impl Default for MobSpec {
             habitat: EVERYWHERE,
             power: 0,
             intrinsics: Vec::new(),
+            resistances: Vec::new(),
+            ranged_range: 0,
+            ranged_power: 0,
+            mana: 0,
+            spells: Vec::new(),
+            on_death: Vec::new(),
+            loot: LootTable::default(),
             shout: ShoutType::Silent,
+            alignment: Alignment::Enemy,
         }
     }
 }
 
 impl Distribution<ExternalEntity> for MobSpec {
     fn sample(&self, _: &mut Rng) -> ExternalEntity {
-        ExternalEntity::new(
-            Loadout::default()
-                .c(StatsComponent::new(Stats::new(
-                    self.power,
-                    &self.intrinsics,
-                )))
-                .c(Desc::new(&self.name, self.icon))
-                .c(Brain::enemy().shout(self.shout))
-                .c(Anim::default())
-                .c(Health::default())
-                .c(Statuses::default()),
-        )
+        let mut stats = Stats::new(self.power, &self.intrinsics)
+            .ranged_range(self.ranged_range)
+            .ranged_power(self.ranged_power)
+            .mana(self.mana)
+            .spells(&self.spells);
+        for &(damage, amount) in &self.resistances {
+            stats = stats.resist(damage, amount);
+        }
+
+        let mut loadout = Loadout::default()
+            .c(StatsComponent::new(stats))
+            .c(Desc::new(&self.name, self.icon))
+            .c(Brain::enemy().shout(self.shout).alignment(self.alignment))
+            .c(Anim::default())
+            .c(Health::default())
+            .c(Statuses::default());
+        let mut on_death = self.on_death.clone();
+        if self.loot.chance > 0 {
+            on_death.push(DeathEffect::Loot(self.loot.clone()));
+        }
+        if !on_death.is_empty() {
+            loadout = loadout.c(on_death);
+        }
+
+        ExternalEntity::new(loadout)
     }
 }
 
@@ -115,8 +158,16 @@ pub struct ItemSpec {
     armor: i32,
     attack: i32,
     defense: i32,
+    ranged_range: u32,
+    ranged_power: i32,
     intrinsics: Vec<Intrinsic>,
     stacks: bool,
+    /// How many items are found in one stack.
+    stack_size: u32,
+    /// Percent chance of the item being cursed.
+    curse_chance: u32,
+    /// What it takes to equip the item.
+    requirements: Requirements,
 }
 
 impl Default for ItemSpec {
@@ -127,34 +178,46 @@ impl Default for ItemSpec {
             depth: 0,
             rarity: 1.0,
             // As a rule, you don't find items laying around in the wilderness.
-            habitat: DUNGEON,
+            habitat: UNDERGROUND,
             item_type: ItemType::MeleeWeapon,
             power: 0,
             armor: 0,
             attack: 0,
             defense: 0,
+            ranged_range: 0,
+            ranged_power: 0,
             intrinsics: Vec::new(),
             stacks: false,
+            stack_size: 1,
+            curse_chance: 0,
+            requirements: Requirements::default(),
         }
     }
 }
 
 impl Distribution<ExternalEntity> for ItemSpec {
-    fn sample(&self, _: &mut Rng) -> ExternalEntity {
+    fn sample(&self, rng: &mut Rng) -> ExternalEntity {
+        let cursed = self.curse_chance > 0 && rng.gen_range(0..100) < self.curse_chance;
         let mut loadout = Loadout::default()
             .c(Desc::new(&self.name, self.icon))
             .c(StatsComponent::new(
                 Stats::new(self.power, &self.intrinsics)
                     .armor(self.armor)
                     .attack(self.attack)
-                    .defense(self.defense),
+                    .defense(self.defense)
+                    .ranged_range(self.ranged_range)
+                    .ranged_power(self.ranged_power),
             ))
             .c(Item {
                 item_type: self.item_type,
                 charges: 1,
+                cursed,
+                requirements: self.requirements,
             });
         if self.stacks {
-            loadout = loadout.c(Stacking::default());
+            loadout = loadout.c(Stacking {
+                count: self.stack_size,
+            });
         }
         ExternalEntity::new(loadout)
     }
@@ -181,8 +244,23 @@ macro_rules! specs {
     }
 }
 
+macro_rules! abilities {
+    {$($ability:ident: $spec:expr,)+}
+    =>
+    {
+        lazy_static! {
+            pub static ref ABILITIES: HashMap<Ability, AbilitySpec> = {
+                let mut ret = HashMap::new();
+                $(ret.insert(Ability::$ability, $spec);)+
+                ret
+            };
+        }
+    }
+}
+
 pub fn iter_specs() -> impl Iterator<Item = Arc<dyn Spec + 'static>> { SPECS.values().cloned() }
 
+use self::Alignment::*;
 use self::Intrinsic::*;
 use self::ShoutType::*;
 use crate::effect::Ability::*;
@@ -197,33 +275,39 @@ specs! {
         rarity: 0.0,
         power: 10,
         intrinsics: vec![Hands],
+        mana: 12,
+        spells: vec![LightningBolt, Confuse],
         shout: Shout,
         ..d()
     },
     MobSpec {
         name: "dreg".into(),
         icon: I::Dreg,
-        habitat: DUNGEON,
+        habitat: DUNGEON | SEWER,
         power: 2,
         intrinsics: vec![Hands],
+        loot: LootTable::new(10, &[("sword", 2), ("arrow", 3), ("scroll of lightning", 1)]),
         shout: Shout,
         ..d()
     },
     MobSpec {
         name: "snake".into(),
         icon: I::Snake,
-        habitat: DUNGEON | TEMPERATE | ARID | URBAN,
+        habitat: DUNGEON | TEMPERATE | ARID | URBAN | CAVE | TOMB,
         power: 1,
         shout: Hiss,
+        alignment: Animal,
         ..d()
     },
     MobSpec {
         name: "ooze".into(),
         icon: I::Ooze,
         depth: 1,
-        habitat: DUNGEON,
+        habitat: DUNGEON | SEWER,
         power: 3,
         shout: Gurgle,
+        resistances: vec![(Damage::Fire, -50)],
+        on_death: vec![DeathEffect::Split { count: 2 }],
         ..d()
     },
     MobSpec {
@@ -232,13 +316,14 @@ specs! {
         depth: 2,
         rarity: 10.0,
         power: 2,
+        alignment: Animal,
         ..d()
     },
     MobSpec {
         name: "octopus|octopi".into(),
         icon: I::Octopus,
         depth: 2,
-        habitat: DUNGEON | TEMPERATE,
+        habitat: DUNGEON | TEMPERATE | SEWER,
         power: 5,
         intrinsics: vec![Hands],
         ..d()
@@ -248,9 +333,10 @@ specs! {
         icon: I::Ogre,
         depth: 5,
         rarity: 4.0,
-        habitat: DUNGEON | ARID,
+        habitat: DUNGEON | ARID | CAVE,
         power: 7,
         intrinsics: vec![Hands],
+        loot: LootTable::new(30, &[("sword", 3), ("helmet", 2), ("armor", 1)]),
         shout: Shout,
         ..d()
     },
@@ -258,30 +344,41 @@ specs! {
         name: "wraith".into(),
         icon: I::Wraith,
         depth: 6,
-        habitat: DUNGEON,
+        habitat: DUNGEON | TOMB,
         rarity: 6.0,
         power: 10,
         intrinsics: vec![Hands],
+        loot: LootTable::new(
+            20,
+            &[
+                ("scroll of fear", 2),
+                ("wand of blinding", 1),
+                ("wand of sleep", 1),
+            ],
+        ),
+        alignment: Undead,
         ..d()
     },
     MobSpec {
         name: "efreeti|efreet".into(),
         icon: I::Efreet,
         depth: 7,
-        habitat: DUNGEON,
+        habitat: DUNGEON | TOMB,
         rarity: 8.0,
         power: 14,
-        intrinsics: vec![Hands],
+        intrinsics: vec![Hands, FireImmunity],
+        on_death: vec![DeathEffect::Terrain(Terrain::Magma)],
         ..d()
     },
     MobSpec {
         name: "serpent".into(),
         icon: I::Serpent,
         depth: 9,
-        habitat: DUNGEON,
+        habitat: DUNGEON | SEWER,
         rarity: 10.0,
         power: 20,
         shout: Hiss,
+        resistances: vec![(Damage::Fire, 50)],
         ..d()
     },
     MobSpec {
@@ -291,23 +388,25 @@ specs! {
         habitat: TEMPERATE,
         power: 7,
         shout: Roar,
+        alignment: Animal,
         ..d()
     },
     MobSpec {
         name: "spider".into(),
         icon: I::Spider,
         depth: 4,
-        habitat: DUNGEON | ARID,
+        habitat: DUNGEON | ARID | CAVE | TOMB,
         power: 20,
         rarity: 10.0,
         shout: Hiss,
+        alignment: Animal,
         ..d()
     },
     MobSpec {
         name: "totem guardian".into(),
         icon: I::TotemGuardian,
         depth: 4,
-        habitat: DUNGEON,
+        habitat: DUNGEON | TOMB,
         rarity: 5.0,
         power: 5,
         intrinsics: vec![Hands],
@@ -316,9 +415,10 @@ specs! {
     MobSpec {
         name: "bat".into(),
         icon: I::Bat,
-        habitat: DUNGEON | TEMPERATE | URBAN | ARID,
+        habitat: DUNGEON | TEMPERATE | URBAN | ARID | SEWER | CAVE,
         power: 1,
         intrinsics: vec![Hyperactive],
+        alignment: Animal,
         ..d()
     },
     MobSpec {
@@ -330,42 +430,48 @@ specs! {
         rarity: 3.0,
         shout: Shout,
         intrinsics: vec![Hands],
+        ranged_range: 5,
+        ranged_power: 3,
+        loot: LootTable::new(20, &[("arrow", 4), ("bow", 1)]),
         ..d()
     },
     MobSpec {
         name: "armored construct".into(),
         icon: I::ArmorConstruct,
-        habitat: DUNGEON,
+        habitat: DUNGEON | TOMB,
         depth: 6,
         power: 8,
         rarity: 5.0,
         intrinsics: vec![Hands],
+        on_death: vec![DeathEffect::Drop("armor".into())],
         ..d()
     },
     MobSpec {
         name: "curse skull".into(),
         icon: I::CurseSkull,
-        habitat: DUNGEON,
+        habitat: DUNGEON | TOMB,
         depth: 6,
         power: 8,
         rarity: 5.0,
         shout: Shout,
+        alignment: Undead,
         ..d()
     },
     MobSpec {
         name: "gelatinous cube".into(),
         icon: I::GelatinousCube,
-        habitat: DUNGEON,
+        habitat: DUNGEON | SEWER,
         depth: 3,
         power: 5,
         rarity: 3.0,
         shout: Gurgle,
+        resistances: vec![(Damage::Fire, -50), (Damage::Electricity, 50)],
         ..d()
     },
     MobSpec {
         name: "crocodile".into(),
         icon: I::Crocodile,
-        habitat: TEMPERATE | ARID,
+        habitat: TEMPERATE | ARID | SEWER,
         depth: 3,
         power: 5,
         rarity: 2.0,
@@ -375,10 +481,11 @@ specs! {
     MobSpec {
         name: "wisp".into(),
         icon: I::Wisp,
-        habitat: DUNGEON,
+        habitat: DUNGEON | TOMB,
         depth: 5,
         power: 5,
         rarity: 10.0,
+        intrinsics: vec![ElectricityImmunity],
         ..d()
     },
     MobSpec {
@@ -388,6 +495,7 @@ specs! {
         depth: 8,
         power: 10,
         rarity: 10.0,
+        resistances: vec![(Damage::Electricity, 50)],
         ..d()
     },
     MobSpec {
@@ -397,22 +505,31 @@ specs! {
         depth: 10,
         power: 50,
         rarity: 20.0,
+        loot: LootTable::new(
+            100,
+            &[
+                ("wand of fireball", 1),
+                ("wand of flame", 1),
+                ("wand of lightning", 1),
+            ],
+        ),
         ..d()
     },
     MobSpec {
         name: "lizardman|lizardmen".into(),
         icon: I::Lizardman,
-        habitat: TEMPERATE | ARID,
+        habitat: TEMPERATE | ARID | CAVE,
         power: 2,
         rarity: 2.0,
         shout: Shout,
         intrinsics: vec![Hands],
+        loot: LootTable::new(15, &[("arrow", 3), ("bow", 1)]),
         ..d()
     },
     MobSpec {
         name: "centipede".into(),
         icon: I::Centipede,
-        habitat: DUNGEON,
+        habitat: DUNGEON | CAVE,
         depth: 8,
         power: 10,
         rarity: 8.0,
@@ -421,14 +538,14 @@ specs! {
     MobSpec {
         name: "floating eye".into(),
         icon: I::FloatingEye,
-        habitat: DUNGEON,
+        habitat: DUNGEON | CAVE,
         power: 2,
         ..d()
     },
     MobSpec {
         name: "eye horror".into(),
         icon: I::EyeHorror,
-        habitat: DUNGEON,
+        habitat: DUNGEON | CAVE,
         depth: 6,
         power: 8,
         rarity: 3.0,
@@ -454,7 +571,7 @@ specs! {
     MobSpec {
         name: "rat".into(),
         icon: I::Rat,
-        habitat: DUNGEON | TEMPERATE | ARID,
+        habitat: DUNGEON | TEMPERATE | ARID | SEWER | CAVE,
         power: 1,
         ..d()
     },
@@ -467,6 +584,11 @@ specs! {
         item_type: MeleeWeapon,
         rarity: 10.0,
         attack: 6,
+        curse_chance: 10,
+        requirements: Requirements {
+            hands: true,
+            ..d()
+        },
         ..d()
     },
     ItemSpec {
@@ -475,6 +597,7 @@ specs! {
         item_type: Helmet,
         rarity: 10.0,
         armor: 2,
+        curse_chance: 10,
         ..d()
     },
     ItemSpec {
@@ -483,6 +606,73 @@ specs! {
         item_type: Armor,
         rarity: 10.0,
         armor: 5,
+        curse_chance: 10,
+        ..d()
+    },
+    ItemSpec {
+        name: "greatsword".into(),
+        icon: I::Sword,
+        item_type: MeleeWeapon,
+        depth: 3,
+        rarity: 10.0,
+        attack: 10,
+        curse_chance: 10,
+        requirements: Requirements {
+            level: 3,
+            hands: true,
+            two_handed: true,
+            ..d()
+        },
+        ..d()
+    },
+    ItemSpec {
+        name: "shield".into(),
+        icon: I::Shield,
+        item_type: Shield,
+        rarity: 10.0,
+        defense: 3,
+        curse_chance: 10,
+        requirements: Requirements {
+            hands: true,
+            ..d()
+        },
+        ..d()
+    },
+    ItemSpec {
+        name: "plate armor".into(),
+        icon: I::Armor,
+        item_type: Armor,
+        depth: 5,
+        rarity: 15.0,
+        armor: 9,
+        curse_chance: 10,
+        requirements: Requirements {
+            power: 16,
+            ..d()
+        },
+        ..d()
+    },
+    ItemSpec {
+        name: "bow".into(),
+        icon: I::Bow,
+        item_type: RangedWeapon,
+        ranged_range: 6,
+        ranged_power: 5,
+        rarity: 5.0,
+        curse_chance: 10,
+        requirements: Requirements {
+            hands: true,
+            ..d()
+        },
+        ..d()
+    },
+    ItemSpec {
+        name: "arrow".into(),
+        icon: I::Arrow,
+        item_type: Ammo,
+        rarity: 2.0,
+        stacks: true,
+        stack_size: 12,
         ..d()
     },
     ItemSpec {
@@ -511,6 +701,165 @@ specs! {
         stacks: true,
         ..d()
     },
+    ItemSpec {
+        name: "scroll of summoning|scrolls of summoning".into(),
+        icon: I::Scroll1,
+        power: 3,
+        item_type: UntargetedUsable(Summon),
+        rarity: 5.0,
+        stacks: true,
+        ..d()
+    },
+    ItemSpec {
+        name: "wand of flame|wands of flame".into(),
+        icon: I::Wand1,
+        power: 5,
+        item_type: TargetedUsable(FlameJet),
+        rarity: 5.0,
+        depth: 4,
+        ..d()
+    },
+    ItemSpec {
+        name: "wand of lightning|wands of lightning".into(),
+        icon: I::Wand2,
+        power: 5,
+        item_type: TargetedUsable(LightningBeam),
+        rarity: 5.0,
+        depth: 4,
+        ..d()
+    },
+    ItemSpec {
+        name: "wand of sleep|wands of sleep".into(),
+        icon: I::Wand2,
+        power: 5,
+        item_type: TargetedUsable(Sleep),
+        rarity: 5.0,
+        depth: 2,
+        ..d()
+    },
+    ItemSpec {
+        name: "wand of poison|wands of poison".into(),
+        icon: I::Wand1,
+        power: 5,
+        item_type: TargetedUsable(Poison),
+        rarity: 5.0,
+        depth: 2,
+        ..d()
+    },
+    ItemSpec {
+        name: "wand of blinding|wands of blinding".into(),
+        icon: I::Wand2,
+        power: 5,
+        item_type: TargetedUsable(Blind),
+        rarity: 5.0,
+        depth: 3,
+        ..d()
+    },
+    ItemSpec {
+        name: "scroll of fear|scrolls of fear".into(),
+        icon: I::Scroll1,
+        power: 2,
+        item_type: TargetedUsable(Terrify),
+        rarity: 5.0,
+        stacks: true,
+        ..d()
+    },
+    ItemSpec {
+        name: "scroll of regeneration|scrolls of regeneration".into(),
+        icon: I::Scroll1,
+        power: 2,
+        item_type: UntargetedUsable(Regenerate),
+        rarity: 5.0,
+        stacks: true,
+        ..d()
+    },
+}
+
+abilities! {
+    LightningBolt: AbilitySpec {
+        targeting: Targeting::Random,
+        range: 4,
+        effects: vec![Effect::Hit { amount: 12, damage: Damage::Electricity }],
+        noise: EXPLOSION_LOUDNESS,
+        message: Some("There is a peal of thunder."),
+        mana_cost: 6,
+        ..d()
+    },
+    Summon: AbilitySpec {
+        targeting: Targeting::Caster,
+        effects: vec![Effect::Summon("dog")],
+        mana_cost: 10,
+        ..d()
+    },
+    Fireball: AbilitySpec {
+        targeting: Targeting::Ball { radius: 1 },
+        range: 9,
+        effects: vec![Effect::Hit { amount: 6, damage: Damage::Fire }],
+        projectile: Some(AnimState::Firespell),
+        impact: Some(AnimState::Explosion),
+        noise: EXPLOSION_LOUDNESS,
+        mana_cost: 8,
+        ..d()
+    },
+    Confuse: AbilitySpec {
+        targeting: Targeting::Bolt,
+        range: 9,
+        effects: vec![Effect::Confuse],
+        mana_cost: 4,
+        ..d()
+    },
+    FlameJet: AbilitySpec {
+        targeting: Targeting::Cone,
+        range: 3,
+        effects: vec![Effect::Hit { amount: 5, damage: Damage::Fire }],
+        impact: Some(AnimState::Explosion),
+        noise: EXPLOSION_LOUDNESS,
+        mana_cost: 8,
+        ..d()
+    },
+    LightningBeam: AbilitySpec {
+        targeting: Targeting::Beam,
+        range: 7,
+        effects: vec![Effect::Hit { amount: 8, damage: Damage::Electricity }],
+        message: Some("There is a peal of thunder."),
+        noise: EXPLOSION_LOUDNESS,
+        mana_cost: 8,
+        ..d()
+    },
+    Sleep: AbilitySpec {
+        targeting: Targeting::Ball { radius: 1 },
+        range: 9,
+        effects: vec![Effect::Sleep],
+        mana_cost: 6,
+        ..d()
+    },
+    Poison: AbilitySpec {
+        targeting: Targeting::Bolt,
+        range: 9,
+        effects: vec![Effect::Poison],
+        mana_cost: 4,
+        ..d()
+    },
+    Blind: AbilitySpec {
+        targeting: Targeting::Bolt,
+        range: 9,
+        effects: vec![Effect::Blind],
+        mana_cost: 4,
+        ..d()
+    },
+    Terrify: AbilitySpec {
+        targeting: Targeting::Cone,
+        range: 4,
+        effects: vec![Effect::Frighten],
+        mana_cost: 6,
+        ..d()
+    },
+    Regenerate: AbilitySpec {
+        targeting: Targeting::Caster,
+        effects: vec![Effect::Regenerate],
+        mana_cost: 8,
+        ..d()
+    },
 }
 
 /// String that's guaranteed to describe an entity spawn.
@@ -522,6 +871,29 @@ impl fmt::Display for EntitySpawn {
 }
 
 lazy_static! {
+    /// Contents of treasure vaults.
+    pub static ref TREASURE: LootTable = LootTable::new(
+        100,
+        &[
+            ("sword", 4),
+            ("helmet", 4),
+            ("armor", 4),
+            ("bow", 2),
+            ("arrow", 2),
+            ("wand of fireball", 2),
+            ("wand of flame", 2),
+            ("wand of lightning", 2),
+            ("wand of sleep", 2),
+            ("wand of poison", 2),
+            ("wand of blinding", 2),
+            ("scroll of regeneration", 2),
+            ("scroll of summoning", 1),
+            ("greatsword", 2),
+            ("shield", 3),
+            ("plate armor", 1),
+        ],
+    );
+
     pub static ref PLAYER_SPAWN: EntitySpawn = EntitySpawn("player".to_string());
 }
 
@@ -598,4 +970,32 @@ mod test {
         // Names not in database don't.
         assert!(ron::de::from_str::<EntitySpawn>(&"\"tyop txet\"".to_string()).is_err());
     }
+
+    #[test]
+    fn test_loot_tables() {
+        use super::{iter_specs, EntitySpawn, TREASURE};
+        use crate::{DeathEffect, LootTable};
+        use std::str::FromStr;
+
+        let mut tables: Vec<LootTable> = vec![TREASURE.clone()];
+        for spec in iter_specs() {
+            let e = spec.sample(&mut calx::seeded_rng(&1));
+            for effect in e.loadout.on_death.into_iter().flatten() {
+                if let DeathEffect::Loot(table) = effect {
+                    tables.push(table);
+                }
+            }
+        }
+        assert!(tables.len() > 1);
+
+        for table in &tables {
+            for (name, _) in &table.items {
+                assert!(
+                    EntitySpawn::from_str(name).is_ok(),
+                    "Unknown loot item {}",
+                    name
+                );
+            }
+        }
+    }
 }
@@ -0,0 +1,146 @@
+//! Deterministic hashing of game state
+
+use crate::{world, World};
+use std::hash::{Hash, Hasher};
+
+impl World {
+    /// Return a hash of the logical game state.
+    ///
+    /// Two worlds that have been given the same seed and commands will have the same hash, so
+    /// this can be used to detect when game logic changes alter simulation outcomes. The hash is
+    /// independent of hash container iteration order and of the animation clock, which runs at
+    /// display speed.
+    pub fn state_hash(&self) -> u64 {
+        let mut h = StableHasher::default();
+
+        for &e in self.ecs.iter() {
+            e.hash(&mut h);
+            world::hash_components(&self.ecs, e, &mut h);
+        }
+
+        self.spatial.hash(&mut h);
+        self.terrain_changes.hash(&mut h);
+        self.identified.hash(&mut h);
+
+        let flags = &self.flags;
+        (
+            flags.camera,
+            flags.tick,
+            flags.player_acted,
+            flags.player,
+            flags.depth,
+            flags.kills,
+        )
+            .hash(&mut h);
+
+        let mut spawns: Vec<_> = self.generated_spawns.iter().collect();
+        spawns.sort();
+        spawns.hash(&mut h);
+
+        // The generator doesn't implement Hash, use its serialized state.
+        ron::ser::to_string(&self.rng)
+            .expect("Failed to serialize rng")
+            .hash(&mut h);
+
+        h.finish()
+    }
+}
+
+/// FNV-1a hasher.
+///
+/// Used instead of the standard library hasher since the hashes are saved in files and compared
+/// against values stored in tests, and must not change between builds. Integers are always hashed
+/// as little-endian and `usize` and `isize` as 64 bits so that the hash doesn't depend on the
+/// platform either.
+pub(crate) struct StableHasher(u64);
+
+impl Default for StableHasher {
+    fn default() -> Self { StableHasher(0xcbf2_9ce4_8422_2325) }
+}
+
+impl Hasher for StableHasher {
+    fn finish(&self) -> u64 { self.0 }
+
+    fn write(&mut self, bytes: &[u8]) {
+        for &b in bytes {
+            self.0 ^= b as u64;
+            self.0 = self.0.wrapping_mul(0x0100_0000_01b3);
+        }
+    }
+
+    fn write_u8(&mut self, i: u8) { self.write(&[i]) }
+    fn write_u16(&mut self, i: u16) { self.write(&i.to_le_bytes()) }
+    fn write_u32(&mut self, i: u32) { self.write(&i.to_le_bytes()) }
+    fn write_u64(&mut self, i: u64) { self.write(&i.to_le_bytes()) }
+    fn write_u128(&mut self, i: u128) { self.write(&i.to_le_bytes()) }
+    fn write_usize(&mut self, i: usize) { self.write_u64(i as u64) }
+    fn write_i8(&mut self, i: i8) { self.write_u8(i as u8) }
+    fn write_i16(&mut self, i: i16) { self.write_u16(i as u16) }
+    fn write_i32(&mut self, i: i32) { self.write_u32(i as u32) }
+    fn write_i64(&mut self, i: i64) { self.write_u64(i as u64) }
+    fn write_i128(&mut self, i: i128) { self.write_u128(i as u128) }
+    fn write_isize(&mut self, i: isize) { self.write_i64(i as i64) }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::location_set::LocationSet;
+    use crate::{Command, ExternalEntity, Location, Runner, World, WorldSeed, WorldSkeleton};
+    use calx::Dir6;
+    use std::hash::{Hash, Hasher};
+
+    fn hash(x: &impl Hash) -> u64 {
+        let mut h = super::StableHasher::default();
+        x.hash(&mut h);
+        h.finish()
+    }
+
+    fn run(rng_seed: u32) -> Runner {
+        let mut runner = Runner::new(WorldSeed {
+            rng_seed,
+            world_skeleton: WorldSkeleton::dungeon_dive(),
+            player_character: ExternalEntity::from_name("player").unwrap(),
+        });
+        for &dir in Dir6::iter().cycle().take(50) {
+            runner.command(Command::Step(dir));
+        }
+        runner
+    }
+
+    #[test]
+    fn test_location_set_hash() {
+        let locs: Vec<Location> = (0..100).map(|i| Location::new(i * 7, -i * 3, 0)).collect();
+        let mut a = LocationSet::default();
+        let mut b = LocationSet::default();
+        a.extend(locs.iter().cloned());
+        b.extend(locs.iter().rev().cloned());
+        assert_eq!(hash(&a), hash(&b));
+    }
+
+    #[test]
+    fn test_platform_independent_hash() {
+        let bytes = |b: &[u8]| {
+            let mut h = super::StableHasher::default();
+            h.write(b);
+            h.finish()
+        };
+        assert_eq!(hash(&0x0102_0304u32), bytes(&[4, 3, 2, 1]));
+        assert_eq!(hash(&7usize), hash(&7u64));
+        assert_eq!(hash(&-2isize), hash(&-2i64));
+    }
+
+    #[test]
+    fn test_state_hash() {
+        let (a, b) = (run(1), run(1));
+        assert_eq!(a.world().state_hash(), b.world().state_hash());
+        assert_ne!(a.world().state_hash(), run(2).world().state_hash());
+
+        // Snapshot of simulation outcome, if this changes, game logic has changed.
+        assert_eq!(a.world().state_hash(), 15120472451442051085);
+
+        // Hash survives save and load.
+        let saved = ron::ser::to_string(a.world()).unwrap();
+        let loaded: World = ron::de::from_str(&saved).unwrap();
+        assert_eq!(loaded.state_hash(), a.world().state_hash());
+    }
+}
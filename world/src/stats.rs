@@ -1,6 +1,6 @@
 use crate::{
-    attack_damage, effect::Damage, msg, roll, Ability, ActionOutcome, AnimState, ItemType, Slot,
-    World,
+    attack_damage, effect::Damage, msg, noise::MELEE_LOUDNESS, roll, Ability, ActionOutcome,
+    AnimState, ItemType, Slot, World,
 };
 use calx::Dir6;
 use calx_ecs::Entity;
@@ -13,7 +13,7 @@ use std::ops::Add;
 /// together to build composites. The Default value for Stats must be an
 /// algebraic zero element, adding it to any Stats value must leave that value
 /// unchanged.
-#[derive(Copy, Clone, Eq, PartialEq, Debug, Default, Serialize, Deserialize)]
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug, Default, Serialize, Deserialize)]
 pub struct Stats {
     /// Generic power level
     pub base_power: i32,
@@ -23,8 +23,17 @@ pub struct Stats {
     pub base_defense: i32,
     /// Damage reduction
     pub armor: i32,
+    /// Percentage of fire damage resisted, negative values are vulnerability.
+    #[serde(default)]
+    pub fire_resist: i32,
+    /// Percentage of electricity damage resisted, negative values are vulnerability.
+    #[serde(default)]
+    pub electricity_resist: i32,
     /// Mana pool / mana drain
     pub mana: i32,
+    /// Bit flags for spells that can be cast with mana
+    #[serde(default)]
+    pub spells: u32,
     /// Ranged attack range. Zero means no ranged capability.
     pub ranged_range: u32,
     /// Ranged attack power
@@ -51,6 +60,12 @@ impl Stats {
     }
 
     pub fn mana(self, mana: i32) -> Stats { Stats { mana, ..self } }
+    pub fn spells(self, spells: &[Ability]) -> Stats {
+        let spells = spells
+            .iter()
+            .fold(self.spells, |acc, &a| acc | (1 << a as u32));
+        Stats { spells, ..self }
+    }
     pub fn armor(self, armor: i32) -> Stats { Stats { armor, ..self } }
     pub fn attack(self, base_attack: i32) -> Stats {
         Stats {
@@ -77,6 +92,26 @@ impl Stats {
         }
     }
 
+    /// Add resistance against a damage type, use a negative amount for vulnerability.
+    pub fn resist(mut self, damage: Damage, amount: i32) -> Stats {
+        match damage {
+            // Physical damage is handled by armor.
+            Damage::Physical => {}
+            Damage::Fire => self.fire_resist += amount,
+            Damage::Electricity => self.electricity_resist += amount,
+        }
+        self
+    }
+
+    /// Return the percentage of damage of a type that is resisted.
+    pub fn resistance(&self, damage: Damage) -> i32 {
+        match damage {
+            Damage::Physical => 0,
+            Damage::Fire => self.fire_resist,
+            Damage::Electricity => self.electricity_resist,
+        }
+    }
+
     pub fn add_intrinsic(&mut self, intrinsic: Intrinsic) {
         self.intrinsics |= 1 << intrinsic as u32;
     }
@@ -91,7 +126,10 @@ impl Add<Stats> for Stats {
             base_attack: self.base_attack + other.base_attack,
             base_defense: self.base_defense + other.base_defense,
             armor: self.armor + other.armor,
+            fire_resist: self.fire_resist + other.fire_resist,
+            electricity_resist: self.electricity_resist + other.electricity_resist,
             mana: self.mana + other.mana,
+            spells: self.spells | other.spells,
             // XXX: Must be careful to have exactly one "ranged weapon" item
             // in the mix. A mob with a natural ranged attack equipping a
             // ranged weapon should *not* have the ranges added together.
@@ -108,8 +146,11 @@ impl Add<Stats> for Stats {
     }
 }
 
-/// Damage state component. The default state is undamaged and unarmored.
-#[derive(Copy, Clone, Eq, PartialEq, Debug, Default, Serialize, Deserialize)]
+/// Depletion state component for hit points, armor and mana.
+///
+/// Tracks what has been used up relative to the entity's stats. The default state is undamaged,
+/// unarmored and with no mana spent.
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug, Default, Serialize, Deserialize)]
 pub struct Health {
     /// The more wounds you have, the more hurt you are. How much damage you
     /// can take before dying depends on entity power level, not described by
@@ -117,13 +158,16 @@ pub struct Health {
     pub wounds: i32,
     /// Armor points get eaten away before you start getting wounds.
     pub armor: i32,
+    /// Mana spent on spells, regenerates over time.
+    #[serde(default)]
+    pub spent_mana: i32,
 }
 
 impl Health {
     pub fn new() -> Health { Default::default() }
 }
 
-#[derive(Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Debug, Serialize, Deserialize)]
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Ord, PartialOrd, Debug, Serialize, Deserialize)]
 /// Temporary creature properties
 pub enum Status {
     /// Creature is acting erratically
@@ -138,12 +182,22 @@ pub enum Status {
     ///
     /// This gets jumped up every time after the creature acted.
     Delayed,
+    /// Takes damage over time.
+    Poisoned,
+    /// Heals over time.
+    Regenerating,
+    /// Magical sleep, can't act until it wears off or the creature gets hurt.
+    Sleeping,
+    /// Can only see adjacent cells.
+    Blind,
+    /// Creature runs away from enemies instead of fighting.
+    Afraid,
 }
 
 pub type Statuses = BTreeMap<Status, u32>;
 
 /// Stats component in the ECS that supports caching applied modifiers for efficiency.
-#[derive(Copy, Clone, Eq, PartialEq, Debug, Default, Serialize, Deserialize)]
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug, Default, Serialize, Deserialize)]
 pub struct StatsComponent {
     /// Base stats that are intrinsic to this entity
     pub base: Stats,
@@ -170,6 +224,10 @@ pub enum Intrinsic {
     Deathsplosion,
     /// Always roaming, can't go to sleep state
     Hyperactive,
+    /// Takes no damage from fire
+    FireImmunity,
+    /// Takes no damage from electricity
+    ElectricityImmunity,
 }
 
 impl World {
@@ -179,6 +237,22 @@ impl World {
 
     pub fn defense(&self, e: Entity) -> i32 { self.stats(e).base_defense + self.stats(e).level * 2 }
 
+    /// Return the percentage of damage of a type the entity resists.
+    ///
+    /// Values over zero reduce damage, 100 is immunity. Values below zero are vulnerability that
+    /// increases damage, down to double damage at -100.
+    pub fn resistance(&self, e: Entity, damage: Damage) -> i32 {
+        let immunity = match damage {
+            Damage::Physical => None,
+            Damage::Fire => Some(Intrinsic::FireImmunity),
+            Damage::Electricity => Some(Intrinsic::ElectricityImmunity),
+        };
+        if immunity.is_some_and(|i| self.has_intrinsic(e, i)) {
+            return 100;
+        }
+        self.stats(e).resistance(damage).clamp(-100, 100)
+    }
+
     /// Return maximum health of an entity.
     pub fn max_hp(&self, e: Entity) -> i32 { self.power(e) }
 
@@ -192,6 +266,32 @@ impl World {
             }
     }
 
+    /// Return size of the mana pool of an entity.
+    pub fn max_mana(&self, e: Entity) -> i32 { self.stats(e).mana.max(0) }
+
+    /// Return current mana of an entity.
+    pub fn mana(&self, e: Entity) -> i32 {
+        self.max_mana(e) - self.ecs().health.get(e).map_or(0, |h| h.spent_mana)
+    }
+
+    /// Return whether the entity can cast a spell with its own mana.
+    pub fn knows_spell(&self, e: Entity, spell: Ability) -> bool {
+        self.stats(e).spells & (1 << spell as u32) != 0
+    }
+
+    /// Return the spells an entity knows.
+    pub fn known_spells(&self, e: Entity) -> Vec<Ability> {
+        Ability::iter()
+            .cloned()
+            .filter(|&a| self.knows_spell(e, a))
+            .collect()
+    }
+
+    /// Return whether the entity can cast a spell right now.
+    pub fn can_cast(&self, e: Entity, spell: Ability) -> bool {
+        self.knows_spell(e, spell) && self.mana(e) >= spell.mana_cost()
+    }
+
     /// Return the (composite) stats for an entity.
     ///
     /// Will return the default value for the Stats type (additive identity in the stat algebra)
@@ -249,6 +349,12 @@ impl World {
             }
         }
 
+        // Innate spells.
+        let spells = self.known_spells(e);
+        if !spells.is_empty() {
+            return spells;
+        }
+
         // Entity has no abilites.
         Vec::new()
     }
@@ -281,6 +387,11 @@ impl World {
 
         // Animate damage
         if hurt {
+            // Pain ends magical sleep.
+            if let Some(statuses) = self.ecs_mut().status.get_mut(e) {
+                statuses.remove(&Status::Sleeping);
+            }
+
             let anim_tick = self.get_anim_tick();
             if let Some(anim) = self.ecs_mut().anim.get_mut(e) {
                 anim.anim_start = anim_tick;
@@ -290,10 +401,14 @@ impl World {
 
         if kill {
             if let Some(attacker) = source {
+                if self.is_player(attacker) {
+                    self.flags.kills += 1;
+                }
                 self.gain_kill_xp(attacker, e);
             }
 
-            if let Some(loc) = self.location(e) {
+            let loc = self.location(e);
+            if let Some(loc) = loc {
                 if self.player_sees(loc) {
                     // TODO: message templating
                     msg!(
@@ -309,6 +424,9 @@ impl World {
                 self.spawn_fx(loc, AnimState::Gib);
             }
             self.kill_entity(e);
+            if let Some(loc) = loc {
+                self.run_death_effects(e, loc);
+            }
         }
     }
 
@@ -329,6 +447,37 @@ impl World {
         }
     }
 
+    /// Spend mana from the entity's pool.
+    ///
+    /// Return false and spend nothing if there isn't enough mana.
+    pub(crate) fn spend_mana(&mut self, e: Entity, amount: i32) -> bool {
+        if self.mana(e) < amount {
+            return false;
+        }
+        match self.ecs_mut().health.get_mut(e) {
+            Some(health) => {
+                health.spent_mana += amount;
+                true
+            }
+            None => amount <= 0,
+        }
+    }
+
+    /// Regain spent mana over time.
+    pub(crate) fn tick_mana_regeneration(&mut self, e: Entity) {
+        const MANA_REGEN_INTERVAL: u64 = 10;
+
+        if !self.get_tick().is_multiple_of(MANA_REGEN_INTERVAL) {
+            return;
+        }
+
+        if let Some(health) = self.ecs_mut().health.get_mut(e) {
+            if health.spent_mana > 0 {
+                health.spent_mana -= 1;
+            }
+        }
+    }
+
     pub(crate) fn gain_status(&mut self, e: Entity, status: Status, duration: u32) {
         if duration == 0 {
             return;
@@ -345,12 +494,27 @@ impl World {
                 statuses.insert(status, duration);
             }
         }
+
+        if status == Status::Blind {
+            self.do_fov(e);
+        }
     }
 
     pub(crate) fn tick_statuses(&mut self, e: Entity) {
-        if let Some(statuses) = self.ecs_mut().status.get_mut(e) {
-            let mut remove = Vec::new();
+        // Damage and healing over time happen once per this many frames.
+        const STATUS_EFFECT_INTERVAL: u64 = 12;
+
+        if self.get_tick().is_multiple_of(STATUS_EFFECT_INTERVAL) {
+            if self.has_status(e, Status::Regenerating) {
+                self.tick_regeneration(e);
+            }
+            if self.has_status(e, Status::Poisoned) {
+                self.damage(e, 1, Damage::Physical, None);
+            }
+        }
 
+        let mut remove = Vec::new();
+        if let Some(statuses) = self.ecs_mut().status.get_mut(e) {
             for (k, d) in statuses.iter_mut() {
                 *d -= 1;
                 if *d == 0 {
@@ -358,13 +522,48 @@ impl World {
                 }
             }
 
-            // TODO: Special stuff when status goes out of effect for dropped statuses.
-            for k in remove.into_iter() {
-                statuses.remove(&k);
+            for k in &remove {
+                statuses.remove(k);
+            }
+        }
+
+        for k in remove.into_iter() {
+            self.lose_status(e, k);
+        }
+    }
+
+    /// Handle a status going out of effect.
+    fn lose_status(&mut self, e: Entity, status: Status) {
+        if status == Status::Blind {
+            self.do_fov(e);
+        }
+
+        if !self.location(e).is_some_and(|loc| self.player_sees(loc)) {
+            return;
+        }
+
+        match status {
+            Status::Sleeping => {
+                msg!("[One] wake[s] up."; self.subject(e));
             }
+            Status::Blind => {
+                msg!("[One] can see again."; self.subject(e));
+            }
+            Status::Afraid => {
+                msg!("[One] regain[s] [one's] courage."; self.subject(e));
+            }
+            _ => {}
         }
     }
 
+    /// Return the temporary statuses currently affecting the entity.
+    pub fn statuses(&self, e: Entity) -> Vec<Status> {
+        self.ecs()
+            .status
+            .get(e)
+            .map_or_else(Vec::new, |s| s.keys().cloned().collect())
+    }
+
     /// Rebuild cached derived stats of an entity.
     ///
     /// Must be explicitly called any time either the entity's base stats or anything relating to
@@ -398,7 +597,9 @@ impl World {
 
     pub(crate) fn really_melee(&mut self, e: Entity, dir: Dir6) -> ActionOutcome {
         let loc = self.location(e)?;
-        let target = self.mob_at(loc.jump(self, dir))?;
+        let target_loc = loc.jump(self, dir);
+        let target = self.mob_at(target_loc)?;
+        self.make_noise(target_loc, MELEE_LOUDNESS, Some(e));
 
         // XXX: Using power stat for damage, should this be different?
         // Do +5 since dmg 1 is really, really useless.
@@ -417,36 +618,96 @@ impl World {
         Some(true)
     }
 
-    fn gain_kill_xp(&mut self, e: Entity, kill: Entity) {
-        let power_diff = self.power(kill) - self.power(e);
-        // XXX: Just threw something together, needs blanning and balancing.
-        let xp = match power_diff {
-            x if x > 2 => 15,
-            2 => 10,
-            1 => 8,
-            0 => 5,
-            -1 => 4,
-            -2 => 2,
-            _ => 1,
-        };
+    pub(crate) fn really_shoot(&mut self, e: Entity, dir: Dir6) -> ActionOutcome {
+        // How many frames a projectile takes to fly over one cell.
+        const PROJECTILE_FRAMES_PER_CELL: u32 = 2;
+
+        let origin = self.location(e)?;
+        let range = self.stats(e).ranged_range;
+        if range == 0 {
+            return None;
+        }
+
+        // Weapons need ammunition, natural ranged attacks don't.
+        if self.entity_equipped(e, Slot::Ranged).is_some() {
+            if let Some(ammo) = self.ammo(e) {
+                self.kill_entity(ammo);
+            } else {
+                if self.is_player(e) {
+                    msg!("[One] [is] out of ammunition."; self.subject(e));
+                }
+                return None;
+            }
+        }
 
+        // Projectile flies until it hits a mob or something solid.
+        let mut loc = origin;
+        let mut target = None;
+        for _ in 0..range {
+            let next = loc.jump(self, dir);
+            if self.terrain(next).blocks_shot() {
+                break;
+            }
+            loc = next;
+            if let Some(mob) = self.mob_at(loc) {
+                target = Some(mob);
+                break;
+            }
+        }
+
+        let projectile = self.spawn_fx(loc, AnimState::Projectile);
+        let distance = origin.distance_from(loc).unwrap_or(0) as u32;
+        self.set_step_tween(projectile, origin, distance * PROJECTILE_FRAMES_PER_CELL);
+
+        if let Some(target) = target {
+            let advantage = self.attack(e) - self.defense(target) + 2 * self.stats(target).armor;
+            let damage = attack_damage(roll(self.rng()), advantage, 5 + self.stats(e).ranged_power);
+
+            if damage == 0 {
+                msg!("[One] miss[es] [another].";
+                    self.subject(e), self.object(target));
+            } else {
+                msg!("[One] shoot[s] [another] for {}.", damage;
+                    self.subject(e), self.object(target));
+            }
+            self.damage(target, damage, Damage::Physical, Some(e));
+        }
+        self.end_turn(e);
+        Some(true)
+    }
+
+    fn gain_kill_xp(&mut self, e: Entity, kill: Entity) {
+        // Killing something as strong as yourself is worth ten points, weaker things are worth
+        // proportionally less and stronger things more.
+        let xp = (self.power(kill) * 10 / self.power(e).max(1)).clamp(1, 100);
         self.gain_xp(e, xp);
     }
 
-    pub(crate) fn gain_xp(&mut self, e: Entity, xp: i32) {
-        const XP_PER_LEVEL: i32 = 100;
+    /// Return the character level of an entity.
+    pub fn level(&self, e: Entity) -> i32 { self.stats(e).level }
 
+    /// Return experience points gained towards the next level.
+    pub fn xp(&self, e: Entity) -> i32 { self.stats(e).xp }
+
+    /// Return experience points needed to advance from the entity's current level.
+    pub fn xp_to_next_level(&self, e: Entity) -> i32 { xp_for_level(self.level(e)) }
+
+    pub(crate) fn gain_xp(&mut self, e: Entity, xp: i32) {
         let mut new_xp = self.stats(e).xp + xp;
 
-        while new_xp >= XP_PER_LEVEL {
+        while new_xp >= self.xp_to_next_level(e) {
+            new_xp -= self.xp_to_next_level(e);
             self.gain_level(e, 1);
-            new_xp -= XP_PER_LEVEL;
         }
 
         // Level drain!
         while new_xp < 0 {
+            if self.level(e) == 0 {
+                new_xp = 0;
+                break;
+            }
             self.gain_level(e, -1);
-            new_xp += XP_PER_LEVEL;
+            new_xp += self.xp_to_next_level(e);
         }
         self.base_stats_mut(e).unwrap().xp = new_xp;
         self.rebuild_stats(e);
@@ -456,22 +717,228 @@ impl World {
         if change == 0 {
             return;
         }
-        if change < 0 {
-            // TODO
-            panic!("Level drain not yet implemented");
-        }
 
         self.base_stats_mut(e).unwrap().level += change;
         self.rebuild_stats(e);
+        let level = self.level(e);
+
+        if change < 0 {
+            if self.is_player(e) {
+                msg!("[One] feel[s] weaker. Welcome to level {}.", level; self.subject(e));
+            } else {
+                msg!("[One] look[s] weaker."; self.subject(e));
+            }
+            self.unequip_unusable(e);
+            return;
+        }
 
         if let Some(health) = self.ecs_mut().health.get_mut(e) {
             health.wounds = 0;
         }
 
         if self.is_player(e) {
-            msg!("[One] feel[s] stronger."; self.subject(e));
+            msg!("[One] feel[s] stronger. Welcome to level {}.", level; self.subject(e));
         } else {
             msg!("[One] look[s] stronger."; self.subject(e));
         }
     }
 }
+
+/// Experience points needed to advance from a level to the next one.
+pub fn xp_for_level(level: i32) -> i32 { 50 * (level + 1) }
+
+#[cfg(test)]
+mod test {
+    use super::{Stats, Status};
+    use crate::effect::{Damage, Effect};
+    use crate::{Ability, ExternalEntity, Slot, World, WorldSeed, WorldSkeleton};
+    use calx::{Dir6, Incremental};
+
+    #[test]
+    fn test_resistances() {
+        // Resistances compose additively.
+        let stats =
+            Stats::default().resist(Damage::Fire, 50) + Stats::default().resist(Damage::Fire, -20);
+        assert_eq!(stats.resistance(Damage::Fire), 30);
+        assert_eq!(stats.resistance(Damage::Electricity), 0);
+
+        let mut world = World::from_seed(&WorldSeed {
+            rng_seed: 1,
+            world_skeleton: WorldSkeleton::dungeon_dive(),
+            player_character: ExternalEntity::from_name("player").unwrap(),
+        });
+        let player = world.player().unwrap();
+        let loc = world.location(player).unwrap();
+        let fire = Effect::Hit {
+            amount: 4,
+            damage: Damage::Fire,
+        };
+
+        let efreet = world.spawn(&ExternalEntity::from_name("efreeti").unwrap(), loc);
+        assert_eq!(world.resistance(efreet, Damage::Fire), 100);
+        world.apply_effect_to_entity(&fire, efreet, None);
+        assert_eq!(world.hp(efreet), world.max_hp(efreet));
+
+        let ooze = world.spawn(&ExternalEntity::from_name("ooze").unwrap(), loc);
+        world.apply_effect_to_entity(&fire, ooze, None);
+        assert_eq!(world.hp(ooze), world.max_hp(ooze) - 6);
+    }
+
+    #[test]
+    fn test_shooting() {
+        let mut world = World::from_seed(&WorldSeed {
+            rng_seed: 1,
+            world_skeleton: WorldSkeleton::dungeon_dive(),
+            player_character: ExternalEntity::from_name("player").unwrap(),
+        });
+        let player = world.player().unwrap();
+        let origin = world.location(player).unwrap();
+
+        // Find a clear line of fire.
+        let dir = *Dir6::iter()
+            .find(|&&d| {
+                let mut loc = origin;
+                (0..3).all(|_| {
+                    loc = loc.jump(&world, d);
+                    !world.blocks_walk(loc)
+                })
+            })
+            .expect("No room for test");
+        let target_loc = origin.jump(&world, dir).jump(&world, dir).jump(&world, dir);
+
+        // No ranged attack without a weapon.
+        assert!(world.really_shoot(player, dir).is_none());
+
+        let bow = world.spawn(&ExternalEntity::from_name("bow").unwrap(), origin);
+        world.equip_item(bow, player, Slot::Ranged);
+        assert!(world.stats(player).ranged_range > 0);
+
+        // Weapon needs ammo.
+        assert!(world.really_shoot(player, dir).is_none());
+
+        let arrows = world.spawn(&ExternalEntity::from_name("arrow").unwrap(), origin);
+        let slot = world.free_bag_slot(player).unwrap();
+        world.equip_item(arrows, player, slot);
+        let count = world.count(arrows);
+
+        let dreg = world.spawn(&ExternalEntity::from_name("dreg").unwrap(), target_loc);
+        assert!(world.really_shoot(player, dir).is_some());
+        assert_eq!(world.count(arrows), count - 1);
+        // Getting shot makes the target come after you.
+        assert_eq!(
+            world.brain_state(dreg),
+            Some(crate::ai::BrainState::Hunting(player))
+        );
+    }
+
+    #[test]
+    fn test_levelling() {
+        let mut world = World::from_seed(&WorldSeed {
+            rng_seed: 1,
+            world_skeleton: WorldSkeleton::dungeon_dive(),
+            player_character: ExternalEntity::from_name("player").unwrap(),
+        });
+        let player = world.player().unwrap();
+        assert_eq!(world.level(player), 0);
+        let power = world.power(player);
+
+        world.gain_xp(player, super::xp_for_level(0) - 1);
+        assert_eq!(world.level(player), 0);
+
+        // Carry over extra points into the next level.
+        world.gain_xp(player, 3);
+        assert_eq!(world.level(player), 1);
+        assert_eq!(world.xp(player), 2);
+        assert!(world.power(player) > power);
+
+        // Enough for several levels at once.
+        world.gain_xp(player, super::xp_for_level(1) + super::xp_for_level(2));
+        assert_eq!(world.level(player), 3);
+        assert_eq!(world.xp(player), 2);
+
+        world.gain_xp(player, -3);
+        assert_eq!(world.level(player), 2);
+        assert_eq!(world.xp(player), super::xp_for_level(2) - 1);
+    }
+
+    #[test]
+    fn test_spellcasting() {
+        let mut world = World::from_seed(&WorldSeed {
+            rng_seed: 1,
+            world_skeleton: WorldSkeleton::dungeon_dive(),
+            player_character: ExternalEntity::from_name("player").unwrap(),
+        });
+        let player = world.player().unwrap();
+        let spell = Ability::LightningBolt;
+        assert!(world.knows_spell(player, spell));
+        assert!(!world.knows_spell(player, Ability::Fireball));
+        assert!(world.use_ability(player, Ability::Summon).is_none());
+
+        let mana = world.max_mana(player);
+        assert_eq!(world.mana(player), mana);
+        assert!(world.use_ability(player, spell).is_some());
+        assert_eq!(world.mana(player), mana - spell.mana_cost());
+
+        // Cast until out of mana, failed casts cost nothing.
+        while world.can_cast(player, spell) {
+            world.use_ability(player, spell);
+        }
+        let left = world.mana(player);
+        assert!(left < spell.mana_cost());
+        assert!(world.use_ability(player, spell).is_none());
+        assert_eq!(world.mana(player), left);
+
+        // Mana regenerates over time.
+        for _ in 0..100 {
+            world.next_tick();
+        }
+        assert!(world.mana(player) > left);
+    }
+
+    #[test]
+    fn test_statuses() {
+        let mut world = World::from_seed(&WorldSeed {
+            rng_seed: 1,
+            world_skeleton: WorldSkeleton::dungeon_dive(),
+            player_character: ExternalEntity::from_name("player").unwrap(),
+        });
+        let player = world.player().unwrap();
+        let origin = world.location(player).unwrap();
+
+        // Poison hurts over time.
+        let hp = world.hp(player);
+        world.apply_effect_to_entity(&Effect::Poison, player, None);
+        for _ in 0..60 {
+            world.heartbeat(player);
+            world.flags.tick += 1;
+        }
+        assert!(world.hp(player) < hp);
+        assert!(world.statuses(player).contains(&Status::Poisoned));
+
+        // Regeneration heals over time.
+        let hp = world.hp(player);
+        world.ecs_mut().status[player].remove(&Status::Poisoned);
+        world.apply_effect_to_entity(&Effect::Regenerate, player, None);
+        for _ in 0..60 {
+            world.heartbeat(player);
+            world.flags.tick += 1;
+        }
+        assert!(world.hp(player) > hp);
+
+        // Sleeping mobs can't act until hurt.
+        world.apply_effect_to_entity(&Effect::Sleep, player, None);
+        assert!(!world.player_can_act());
+        world.damage(player, 1, Damage::Physical, None);
+        assert!(world.player_can_act());
+
+        // Blindness shrinks the field of view.
+        let far = *world.noise_area(origin, 2).last().unwrap();
+        assert!(world.player_sees(far));
+        world.apply_effect_to_entity(&Effect::Blind, player, None);
+        assert!(!world.player_sees(far));
+        assert!(world.player_sees(origin));
+        world.ecs_mut().status[player].insert(Status::Blind, 1);
+        world.heartbeat(player);
+        assert!(world.player_sees(far));
+    }
+}
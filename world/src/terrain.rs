@@ -12,7 +12,10 @@ impl World {
 
     /// Return terrain at location.
     pub fn terrain(&self, loc: Location) -> Terrain {
-        let mut t = self.world_cache.get_terrain(loc);
+        let mut t = match self.terrain_changes.get(&loc) {
+            Some(&t) => t,
+            None => self.world_cache.get_terrain(loc),
+        };
 
         if t == Terrain::Door && self.has_mobs(loc) {
             // Standing in the doorway opens the door.
@@ -39,6 +42,13 @@ impl World {
         false
     }
 
+    /// Change the terrain at a location for the rest of the game.
+    pub(crate) fn set_terrain(&mut self, loc: Location, terrain: Terrain) {
+        self.terrain_changes.insert(loc, terrain);
+        // Walkability may have changed.
+        self.invalidate_flow_fields();
+    }
+
     /// Return a portal if it can be seen through.
     pub fn visible_portal(&self, loc: Location) -> Option<Location> {
         // Only void-form is transparent to portals.
@@ -28,6 +28,76 @@ vaults! {VAULTS,
       #....#
       ##++##
     ",
+    "
+    #######
+    #$...$#
+    #..a..#
+    #$...$#
+    ###+###
+    ",
+}
+
+vaults! {SEWER_VAULTS,
+    "
+    ##+###+##
+    #.......#
+    #.~~~~~.#
+    +.~~~~~.+
+    #.~~~~~.#
+    #...a...#
+    ##+###+##
+    ",
+    "
+    ###+###
+    #..=..#
+    #..=..#
+    +==I==+
+    #..=..#
+    #.a=..#
+    ###+###
+    ",
+}
+
+vaults! {CAVE_VAULTS,
+    "
+      ##+##
+     ##...##
+    ##..I..##
+    +...$...+
+    ##..I..##
+     ##...##
+      ##+##
+    ",
+    "
+     ###+###
+    ##.....##
+    #..~~~..#
+    +.~~~~~.#
+    #..~~~..#
+    ##..$..##
+     #######
+    ",
+}
+
+vaults! {TOMB_VAULTS,
+    "
+    ###+###
+    #I...I#
+    #..$..#
+    #I...I#
+    #..$..#
+    #I...I#
+    #######
+    ",
+    "
+      ##+##
+      #...#
+    ###I.I###
+    +.......+
+    ###I.I###
+      #.$.#
+      #####
+    ",
 }
 
 vaults! {ENTRANCES,
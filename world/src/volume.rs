@@ -1,5 +1,6 @@
 use crate::{fov::SphereVolumeFov, Location, World};
-use calx::HexFov;
+use calx::{Dir6, HexFov};
+use euclid::vec2;
 use std::iter::FromIterator;
 
 impl World {
@@ -24,4 +25,37 @@ impl Volume {
             HexFov::new(SphereVolumeFov::new(w, radius, origin)).map(|(pos, a)| a.origin + pos),
         ))
     }
+
+    /// Construct a 60 degree cone volume spreading from origin towards the direction.
+    ///
+    /// The origin is not included in the cone.
+    pub fn cone(w: &World, origin: Location, dir: Dir6, radius: u32) -> Volume {
+        let Volume(sphere) = Volume::sphere(w, origin, radius);
+        Volume(
+            sphere
+                .into_iter()
+                .filter(|&loc| {
+                    origin
+                        .v2_at(loc)
+                        .is_some_and(|v| v != vec2(0, 0) && Dir6::from_v2(v) == dir)
+                })
+                .collect(),
+        )
+    }
+
+    /// Construct a line volume from origin towards the direction that is stopped by walls.
+    ///
+    /// The origin is not included in the beam.
+    pub fn beam(w: &World, origin: Location, dir: Dir6, range: u32) -> Volume {
+        let mut ret = Vec::new();
+        let mut loc = origin;
+        for _ in 0..range {
+            loc = loc.jump(w, dir);
+            if w.terrain(loc).blocks_shot() {
+                break;
+            }
+            ret.push(loc);
+        }
+        Volume(ret)
+    }
 }
@@ -1,14 +1,28 @@
 use crate::{
-    ai, animations, components, desc, flags::Flags, item, spatial::Spatial, spec::EntitySpawn,
-    stats, world_cache::WorldCache, Distribution, ExternalEntity, Location, Rng, WorldSkeleton,
+    ai, animations, components, death, desc, flags::Flags, identify::AppearanceCache, item,
+    pathing::FlowFieldCache, spatial::Spatial, spec::EntitySpawn, stats, world_cache::WorldCache,
+    Distribution, ExternalEntity, Location, Rng, Terrain, WorldSkeleton,
 };
 use calx::seeded_rng;
 use serde::{Deserialize, Serialize};
-use std::collections::HashSet;
+use std::collections::{BTreeMap, BTreeSet, HashSet};
+use std::hash::{Hash, Hasher};
 
-pub const GAME_VERSION: &str = "0.1.0";
+pub const GAME_VERSION: &str = "0.4.0";
 
-calx_ecs::build_ecs! {
+/// Declare the ECS along with the operations that go over every component type.
+macro_rules! world_ecs {
+    {$($compname:ident: $comptype:ty,)+} => {
+        calx_ecs::build_ecs! { $($compname: $comptype,)+ }
+
+        /// Feed all components of an entity into a hasher.
+        pub(crate) fn hash_components(ecs: &Ecs, e: calx_ecs::Entity, h: &mut impl Hasher) {
+            $(ecs.$compname.get(e).hash(h);)+
+        }
+    };
+}
+
+world_ecs! {
     anim: animations::Anim,
     brain: ai::Brain,
     desc: desc::Desc,
@@ -18,9 +32,10 @@ calx_ecs::build_ecs! {
     stacking: item::Stacking,
     stats: stats::StatsComponent,
     status: stats::Statuses,
+    on_death: death::DeathEffects,
 }
 
-#[derive(Serialize, Deserialize)]
+#[derive(Clone, Serialize, Deserialize)]
 pub struct WorldSeed {
     pub rng_seed: u32,
     pub world_skeleton: WorldSkeleton,
@@ -38,13 +53,23 @@ pub struct World {
     /// Static startup game world
     pub(crate) world_cache: WorldCache,
     /// Spawns from worldgen that have been generated in world.
-    generated_spawns: HashSet<(Location, EntitySpawn)>,
+    pub(crate) generated_spawns: HashSet<(Location, EntitySpawn)>,
     /// Spatial index for game entities.
     pub(crate) spatial: Spatial,
+    /// Changes to the generated terrain made during play.
+    pub(crate) terrain_changes: BTreeMap<Location, Terrain>,
+    /// Spawn names of the item kinds the player has identified.
+    pub(crate) identified: BTreeSet<String>,
     /// Global gamestate flags.
     pub(crate) flags: Flags,
     /// Persistent random number generator.
     pub(crate) rng: Rng,
+    /// Cached AI pathfinding maps, rebuilt on demand.
+    #[serde(skip)]
+    pub(crate) flow_fields: FlowFieldCache,
+    /// Cached appearances of unidentified items.
+    #[serde(skip)]
+    pub(crate) appearances: AppearanceCache,
 }
 
 impl World {
@@ -55,8 +80,12 @@ impl World {
             world_cache: WorldCache::new(world_seed.rng_seed, world_seed.world_skeleton.clone()),
             generated_spawns: Default::default(),
             spatial: Default::default(),
+            terrain_changes: Default::default(),
+            identified: Default::default(),
             flags: Default::default(),
             rng: seeded_rng(&world_seed.rng_seed),
+            flow_fields: Default::default(),
+            appearances: Default::default(),
         };
 
         ret.spawn_player(
@@ -71,6 +100,10 @@ impl World {
     pub(crate) fn generate_world_spawns(&mut self) {
         let mut spawns = self.world_cache.drain_spawns();
         spawns.retain(|s| !self.generated_spawns.contains(s));
+        if !spawns.is_empty() {
+            // New map sectors got generated, pathing data may be out of date.
+            self.invalidate_flow_fields();
+        }
         let seed = self.rng_seed();
 
         for (loc, s) in &spawns {
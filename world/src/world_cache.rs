@@ -1,7 +1,7 @@
 use crate::{
     location::{Location, Portal},
     map::MapCell,
-    sector::{self, Sector, WorldSkeleton},
+    sector::{self, Sector, SectorDir, SectorSpec, WorldSkeleton},
     spec::EntitySpawn,
     terrain::Terrain,
 };
@@ -27,7 +27,14 @@ pub struct WorldCache {
 
 impl WorldCache {
     /// Initiate the cache given the world description.
+    ///
+    /// Panics if the skeleton is invalid. Skeletons from data files are checked when they're
+    /// loaded, so this should only trip on a bug.
     pub fn new(seed: u32, skeleton: WorldSkeleton) -> WorldCache {
+        if let Err(e) = skeleton.validate() {
+            panic!("Invalid world skeleton: {}", e);
+        }
+
         WorldCache {
             seed,
             skeleton,
@@ -67,6 +74,8 @@ impl WorldCache {
 
     pub fn sector_exists(&self, sector: Sector) -> bool { self.skeleton.contains_key(&sector) }
 
+    pub fn sector_spec(&self, sector: Sector) -> Option<&SectorSpec> { self.skeleton.get(&sector) }
+
     /// Return latest list of spawns.
     ///
     /// `WorldCache` will return spawns from regions that have been loaded into cache. Caller will
@@ -108,12 +117,28 @@ impl WorldCache {
         ) in &map
         {
             let loc = Location::from(sector) + *vec;
+            let mut terrain = *terrain;
+
+            // Remember where the stairs are, and fill in the ones that can only be arrived
+            // through.
+            for &(stairs, dir) in &[
+                (Terrain::Upstairs, SectorDir::Up),
+                (Terrain::Downstairs, SectorDir::Down),
+            ] {
+                if terrain == stairs {
+                    let mut cache = self.internal_cache.borrow_mut();
+                    cache.stairs.entry((sector, dir)).or_insert(loc);
+                    if !self.skeleton.can_travel(sector, dir) {
+                        terrain = Terrain::Ground;
+                    }
+                }
+            }
 
-            if *terrain != Terrain::default() {
+            if terrain != Terrain::default() {
                 self.internal_cache
                     .borrow_mut()
                     .terrain
-                    .insert(loc, *terrain);
+                    .insert(loc, terrain);
             }
 
             // World cache uses (location, spawn string) as the key to see if it already has
@@ -173,16 +198,24 @@ impl WorldCache {
         self.generate(below);
         self.generate(sector);
 
-        if let Some(my_up) = self.upstairs(sector) {
-            let their_down = self
-                .downstairs(above)
-                .expect("No matching downstairs found");
-            self.make_stairs(their_down, my_up);
+        // Each sector makes its own way out, the stairs at the other end of a one-way stairwell
+        // lead nowhere.
+        if self.skeleton.can_travel(sector, SectorDir::Up) {
+            if let Some(my_up) = self.stairs(sector, SectorDir::Up) {
+                let their_down = self
+                    .stairs(above, SectorDir::Down)
+                    .expect("No matching downstairs found");
+                self.portal(my_up, their_down - vec2(1, 1));
+            }
         }
 
-        if let Some(my_down) = self.downstairs(sector) {
-            let their_up = self.upstairs(below).expect("No matching upstairs found");
-            self.make_stairs(my_down, their_up);
+        if self.skeleton.can_travel(sector, SectorDir::Down) {
+            if let Some(my_down) = self.stairs(sector, SectorDir::Down) {
+                let their_up = self
+                    .stairs(below, SectorDir::Up)
+                    .expect("No matching upstairs found");
+                self.portal(my_down, their_up + vec2(1, 1));
+            }
         }
 
         let pending_spawns = self
@@ -204,31 +237,16 @@ impl WorldCache {
             .insert(sector);
     }
 
-    /// Find location of stairs down on sector.
-    fn downstairs(&self, sector: Sector) -> Option<Location> {
-        self.generate(sector);
-        for loc in sector.iter() {
-            if let Some(Terrain::Downstairs) = self.internal_cache.borrow().terrain.get(&loc) {
-                return Some(loc);
-            }
-        }
-        None
-    }
-
-    fn upstairs(&self, sector: Sector) -> Option<Location> {
+    /// Find location of the stairs going up or down in a sector.
+    ///
+    /// Also finds the dead ends of one-way stairwells that have been filled in.
+    fn stairs(&self, sector: Sector, dir: SectorDir) -> Option<Location> {
         self.generate(sector);
-        for loc in sector.iter() {
-            if let Some(Terrain::Upstairs) = self.internal_cache.borrow().terrain.get(&loc) {
-                return Some(loc);
-            }
-        }
-        None
-    }
-
-    /// Make a two-way stairwell portal.
-    fn make_stairs(&self, downstairs: Location, upstairs: Location) {
-        self.portal(upstairs, downstairs - vec2(1, 1));
-        self.portal(downstairs, upstairs + vec2(1, 1));
+        self.internal_cache
+            .borrow()
+            .stairs
+            .get(&(sector, dir))
+            .cloned()
     }
 
     /// Punch a (one-way) portal between two points.
@@ -265,6 +283,8 @@ struct InternalCache {
     finalized_sectors: HashSet<Sector>,
 
     terrain: HashMap<Location, Terrain>,
+    /// Stairwell locations of constructed sectors.
+    stairs: HashMap<(Sector, SectorDir), Location>,
     portals: HashMap<Location, Portal>,
 
     pending_spawns: HashMap<Sector, Vec<(Location, EntitySpawn)>>,